@@ -14,6 +14,7 @@ describe Enquo::Field do
 	let(:opts) { {} }
 	let(:json) { JSON.parse(ciphertext, symbolize_names: true) }
 	let(:v1) { json[:v1] }
+	let(:payload) { v1 }
 
 	describe "#encrypt_boolean" do
 		let(:ciphertext) { field.encrypt_boolean(value, context, **opts) }
@@ -13,7 +13,8 @@ describe Enquo::Field do
 	let(:context) { "test" }
 	let(:opts) { {} }
 	let(:json) { JSON.parse(ciphertext, symbolize_names: true) }
-	let(:v1) { json[:v1] }
+	let(:v2) { json[:v2] }
+	let(:payload) { v2 }
 
 	describe "#encrypt_date" do
 		let(:ciphertext) { field.encrypt_date(value, context, **opts) }
@@ -32,14 +33,14 @@ describe Enquo::Field do
 
 				it_behaves_like "an encrypt function"
 
-				it "is a v1 value" do
-					expect(json).to have_key(:v1)
+				it "is a v2 value" do
+					expect(json).to have_key(:v2)
 				end
 
 				it "contains ORE values" do
-					expect(v1).to have_key(:y)
-					expect(v1).to have_key(:m)
-					expect(v1).to have_key(:d)
+					expect(v2).to have_key(:y)
+					expect(v2).to have_key(:m)
+					expect(v2).to have_key(:d)
 				end
 			end
 		end
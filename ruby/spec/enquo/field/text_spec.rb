@@ -13,7 +13,8 @@ describe Enquo::Field do
 	let(:opts) { {} }
 	let(:context) { "test" }
 	let(:json) { JSON.parse(ciphertext, symbolize_names: true) }
-	let(:v1) { json[:v1] }
+	let(:v2) { json[:v2] }
+	let(:payload) { v2 }
 
 	describe "#encrypt_text" do
 		let(:ciphertext) { field.encrypt_text(value, context, **opts) }
@@ -28,27 +29,27 @@ describe Enquo::Field do
 
 				it_behaves_like "an encrypt function"
 
-				it "is a v1 ciphertext" do
-					expect(json).to have_key(:v1)
+				it "is a v2 ciphertext" do
+					expect(json).to have_key(:v2)
 				end
 
 				it "contains an equality key" do
-					expect(v1).to have_key(:e)
+					expect(v2).to have_key(:e)
 				end
 
 				it "contains a length" do
-					expect(v1).to have_key(:l)
+					expect(v2).to have_key(:l)
 				end
 
 				context "with no_query: true" do
 					let(:opts) { { no_query: true } }
 
 					it "does not contain an equality key" do
-						expect(v1).to_not have_key(:e)
+						expect(v2).to_not have_key(:e)
 					end
 
 					it "does not contain a length" do
-						expect(v1).to_not have_key(:l)
+						expect(v2).to_not have_key(:l)
 					end
 				end
 
@@ -56,7 +57,7 @@ describe Enquo::Field do
 					let(:opts) { { unsafe: true } }
 
 					it "contains a hash code" do
-						expect(v1).to have_key(:h)
+						expect(v2).to have_key(:h)
 					end
 				end
 
@@ -65,7 +66,7 @@ describe Enquo::Field do
 						let(:opts) { { order_prefix_length: 8, unsafe: true } }
 
 						it "contains an ordering ciphertext" do
-							expect(v1).to have_key(:o)
+							expect(v2).to have_key(:o)
 						end
 					end
 
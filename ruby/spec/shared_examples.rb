@@ -16,13 +16,13 @@ shared_examples "an encrypt function" do
 	end
 
 	it "has an AES value" do
-		expect(v1).to have_key(:a)
+		expect(payload).to have_key(:a)
 	end
 
 	it "has a key ID" do
-		expect(v1).to have_key(:k)
-		expect(v1[:k].length).to eq(8)
-		expect(v1[:k]).to all be_between(0, 255)
+		expect(payload).to have_key(:k)
+		expect(payload[:k].length).to eq(8)
+		expect(payload[:k]).to all be_between(0, 255)
 	end
 
 	{
@@ -0,0 +1,367 @@
+//! Collation support
+//!
+//! Figuring out how strings *should* be sorted is a fiddly job.  That's why we've locked it all
+//! away in here.
+//!
+//! The parts of this that are of interest to the outside world are [`Collation`], which
+//! describes *how* a text should be sorted by the built-in collator, when an ordering code is
+//! requested via
+//! [`TextOptions::with_collation()`](crate::datatype::TextOptions::with_collation), and the
+//! [`Collator`] trait, which lets you bring your own sorting rules along via
+//! [`TextOptions::with_collator()`](crate::datatype::TextOptions::with_collator).
+//!
+
+#[cfg(feature = "icu")]
+use {rust_icu_sys::UColAttributeValue, rust_icu_ucol::UCollator, rust_icu_ustring::UChar};
+
+use crate::Error;
+
+#[cfg(feature = "uca")]
+mod uca;
+
+/// What a collator needs to be able to do
+///
+/// A collator turns a text into a "sort key": a sequence of bytes which, when compared with
+/// the sort keys of other texts using plain old byte-by-byte comparison, puts the texts in the
+/// "right" order, whatever that happens to mean for your data.  The built-in implementations are
+/// [`Collation`], which sorts according to the rules of a locale, and [`Bytes`], which just sorts
+/// by the UTF-8 encoding of the text.  If neither of those suits (say, you want part numbers to
+/// sort "naturally", so that `A10` comes after `A9`), you can implement this trait yourself.
+///
+pub trait Collator: Send + Sync + std::fmt::Debug {
+    /// A string which uniquely describes the sorting rules of this collator
+    ///
+    /// The identifier is stored alongside the ordering code of every encrypted text, and texts
+    /// whose ordering codes were generated by collators with different identifiers cannot be
+    /// compared.  Two collators must only have the same identifier if they produce the same sort
+    /// key for every text, and the identifier of a collator must never change, lest it become
+    /// impossible to compare new texts against those already stored.
+    ///
+    fn identifier(&self) -> String;
+
+    /// Generate the sort key for a text
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the text cannot be collated, for whatever reason.
+    ///
+    fn sort_key(&self, text: &str) -> Result<Vec<u8>, Error>;
+}
+
+/// The locale used for collation when nobody has said otherwise
+///
+pub(crate) const DEFAULT_COLLATION_LOCALE: &str = "en";
+
+/// The collation identifier we assume when a ciphertext doesn't say which collation was used
+///
+/// Ordering codes created before the collation could be chosen were all generated with the `en`
+/// locale, by whichever engine the crate was built with: ICU, for a build with the `icu` feature,
+/// and the UTF-8 encoding of the text otherwise.  Since the stored data doesn't say which, we
+/// have to assume it came from a build like this one, so for ICU builds the missing identifier is
+/// that of ICU's default collation.  Those builds, and builds with no collation features at all,
+/// carry on leaving the identifier of their default collation out of new ordering codes, so that
+/// they stay comparable with the old ones.
+///
+/// The `uca` feature didn't exist back then, so there's no legacy data it could have produced;
+/// a UCA build assumes that codes without an identifier are from the UTF-8 fallback, and
+/// records its own identifier in every ordering code, so the two can't be mixed up.
+///
+#[cfg(feature = "icu")]
+pub(crate) const LEGACY_COLLATION_IDENTIFIER: &str = "icu:en";
+/// The collation identifier we assume when a ciphertext doesn't say which collation was used
+#[cfg(not(feature = "icu"))]
+pub(crate) const LEGACY_COLLATION_IDENTIFIER: &str = "en";
+
+/// The name of the engine which generates the sort keys for a [`Collation`]
+///
+/// The same locale and strength produce entirely different sort keys from ICU, the built-in UCA,
+/// and the UTF-8 fallback, so the engine has to be part of a collation's identifier.  The
+/// fallback goes without a name, so that its default collation keeps the identifier under which
+/// its ordering codes have always been stored (see [`LEGACY_COLLATION_IDENTIFIER`]).
+///
+#[cfg(feature = "icu")]
+const COLLATION_ENGINE: Option<&str> = Some("icu");
+/// The name of the engine which generates the sort keys for a [`Collation`]
+#[cfg(all(feature = "uca", not(feature = "icu")))]
+const COLLATION_ENGINE: Option<&str> = Some("uca");
+/// The name of the engine which generates the sort keys for a [`Collation`]
+#[cfg(not(any(feature = "icu", feature = "uca")))]
+const COLLATION_ENGINE: Option<&str> = None;
+
+/// How fussy the collator should be about the differences between texts
+///
+/// These correspond to the comparison levels defined in the Unicode Collation Algorithm.  Each
+/// level takes into account everything the levels before it did, and then some.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Strength {
+    /// Whatever the locale thinks is best; for most locales, this is the same as `Tertiary`
+    #[default]
+    Default,
+    /// Only consider the base letters, so "a", "á", and "A" all sort the same
+    Primary,
+    /// Base letters and accents matter, but case doesn't
+    Secondary,
+    /// Base letters, accents, and case all matter
+    Tertiary,
+    /// As per `Tertiary`, but also distinguishes between (some) punctuation and whitespace that
+    /// would otherwise be ignored
+    Quaternary,
+    /// Every codepoint counts, so the only texts that sort the same are identical ones
+    Identical,
+}
+
+impl Strength {
+    /// The name by which this strength is known in a collation identifier
+    ///
+    fn name(self) -> &'static str {
+        match self {
+            Strength::Default => "default",
+            Strength::Primary => "primary",
+            Strength::Secondary => "secondary",
+            Strength::Tertiary => "tertiary",
+            Strength::Quaternary => "quaternary",
+            Strength::Identical => "identical",
+        }
+    }
+
+    /// The ICU attribute value that asks for this strength
+    ///
+    #[cfg(feature = "icu")]
+    fn icu_value(self) -> UColAttributeValue {
+        match self {
+            Strength::Default => UColAttributeValue::UCOL_DEFAULT,
+            Strength::Primary => UColAttributeValue::UCOL_PRIMARY,
+            Strength::Secondary => UColAttributeValue::UCOL_SECONDARY,
+            Strength::Tertiary => UColAttributeValue::UCOL_TERTIARY,
+            Strength::Quaternary => UColAttributeValue::UCOL_QUATERNARY,
+            Strength::Identical => UColAttributeValue::UCOL_IDENTICAL,
+        }
+    }
+}
+
+/// A description of the rules by which texts are to be sorted
+///
+/// This consists of a locale (such as `de`, `sv`, or `de-u-co-phonebk`), which selects the
+/// language-specific tailoring, and a [`Strength`], which controls which sorts of differences
+/// between texts are considered significant.
+///
+/// Every collation has an [identifier](Collator::identifier), which is stored alongside the
+/// ordering code of an encrypted text, so that texts which were sorted by different rules don't
+/// get compared against each other.  Since the rules depend on which collation engine the crate
+/// was built with, so does the identifier.
+///
+/// ```rust
+/// use enquo_core::collator::{Collation, Collator, Strength};
+///
+/// let c = Collation::new("sv").with_strength(Strength::Secondary);
+/// assert!(c.identifier().ends_with("sv/secondary"));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Collation {
+    /// The locale whose sorting rules are to be followed
+    locale: String,
+    /// How much of a difference between two texts is enough of a difference
+    strength: Strength,
+}
+
+impl Collation {
+    /// Create a new collation for the given locale, with the default strength
+    ///
+    #[must_use]
+    pub fn new(locale: &str) -> Self {
+        Self {
+            locale: locale.to_string(),
+            strength: Strength::Default,
+        }
+    }
+
+    /// Set the strength of the collation
+    ///
+    #[must_use]
+    pub fn with_strength(mut self, strength: Strength) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// The locale this collation follows
+    ///
+    #[must_use]
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// How strong the collation is
+    ///
+    #[must_use]
+    pub fn strength(&self) -> Strength {
+        self.strength
+    }
+}
+
+impl Default for Collation {
+    fn default() -> Self {
+        Self::new(DEFAULT_COLLATION_LOCALE)
+    }
+}
+
+impl Collator for Collation {
+    /// For a collation with the default strength, this is just the locale; otherwise, the
+    /// strength is tacked on the end after a slash (`de/primary`).  Either way, when the crate is
+    /// built with the `icu` or `uca` feature, the name of the engine goes on the front
+    /// (`icu:de/primary`, `uca:en`).
+    ///
+    fn identifier(&self) -> String {
+        let rules = if self.strength == Strength::Default {
+            self.locale.clone()
+        } else {
+            format!("{}/{}", self.locale, self.strength.name())
+        };
+
+        match COLLATION_ENGINE {
+            Some(engine) => format!("{engine}:{rules}"),
+            None => rules,
+        }
+    }
+
+    fn sort_key(&self, text: &str) -> Result<Vec<u8>, Error> {
+        generate_sort_key(text, self)
+    }
+}
+
+/// A collator which sorts texts by their UTF-8 encoding
+///
+/// This is what you get from [`Collation`] when the crate is built without any of the fancier
+/// collation features, but this one does the same thing no matter how the crate is built, which
+/// is handy if you want a guaranteed-stable ordering of, say, identifiers.
+///
+/// ```rust
+/// use enquo_core::collator::{Bytes, Collator};
+///
+/// assert_eq!(b"abc".to_vec(), Bytes.sort_key("abc").unwrap());
+/// ```
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[allow(clippy::exhaustive_structs)] // There's nothing in here to add to later
+pub struct Bytes;
+
+impl Collator for Bytes {
+    fn identifier(&self) -> String {
+        "bytes".to_string()
+    }
+
+    fn sort_key(&self, text: &str) -> Result<Vec<u8>, Error> {
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+/// Create a "sort key" for a given string, using ICU
+///
+/// This is the engine behind the [`Collator`] implementation for [`Collation`].
+///
+/// A sort key is a value that transmogrifies the usual numeric values of characters in such a way
+/// that, when you sort the sort keys using ordinary numeric lowest-to-highest, the corresponding
+/// texts are sorted "correctly".
+///
+/// The problem is that "correctly" varies by language, geography, and even context (German
+/// dictionaries are sorted differently to German telephone books, for example).  Luckily, ICU
+/// ("Internationalization Components for Unicode") provide mechanisms for figuring all that out.
+/// Unluckily, linking ICU into a Rust binary that is to be used by many people is a shitshow
+/// (ICU bumps its soname like there's a prize for the highest version number).
+///
+/// All this is to say that, by default, there's a built-in collator that just returns ASCII
+/// values for the ordering code, but that can be swapped out by building with the `icu` feature,
+/// which instead causes this function to come into existence.  If both the `icu` and `uca`
+/// features are enabled, ICU wins.
+///
+#[cfg(feature = "icu")]
+pub(crate) fn generate_sort_key(text: &str, collation: &Collation) -> Result<Vec<u8>, Error> {
+    let mut collator = UCollator::try_from(collation.locale())
+        .map_err(|e| Error::CollationError(format!("could not create collator: {e}")))?;
+    collator.set_strength(collation.strength().icu_value());
+    let uc_text = UChar::try_from(text)
+        .map_err(|e| Error::CollationError(format!("invalid text string: {e}")))?;
+
+    Ok(collator.get_sort_key(&uc_text))
+}
+
+/// Create a "sort key" for a given string, using the built-in Unicode Collation Algorithm
+///
+/// Building with the `uca` feature gets you a pure-Rust implementation of the UCA, using the
+/// Default Unicode Collation Element Table.  This gives a sensible, language-agnostic ordering
+/// (accented letters sort alongside their base letters, case is a tie-breaker rather than the
+/// main event, and so on) without the pain of linking ICU.  It can't do locale-specific
+/// tailorings, though, so asking for a collation in anything other than English (or the root
+/// locale) is an error.
+///
+#[cfg(all(feature = "uca", not(feature = "icu")))]
+pub(crate) fn generate_sort_key(text: &str, collation: &Collation) -> Result<Vec<u8>, Error> {
+    uca::sort_key(text, collation)
+}
+
+/// The default sort key generator
+///
+/// If you don't want to go through the rigors of building with ICU (or the bloat of the UCA
+/// tables), you can use this collator, which sucks to a great degree, but provides at least a
+/// stable sort order.
+///
+#[cfg(not(any(feature = "icu", feature = "uca")))]
+#[allow(clippy::unnecessary_wraps)] // The ICU version of this function can crap out
+pub(crate) fn generate_sort_key(text: &str, _collation: &Collation) -> Result<Vec<u8>, Error> {
+    Ok(text.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(any(feature = "icu", feature = "uca")))]
+    #[test]
+    fn fallback_identifiers_are_unadorned() {
+        assert_eq!(
+            LEGACY_COLLATION_IDENTIFIER,
+            Collation::default().identifier()
+        );
+        assert_eq!(
+            "sv/secondary",
+            Collation::new("sv")
+                .with_strength(Strength::Secondary)
+                .identifier()
+        );
+    }
+
+    #[cfg(all(feature = "uca", not(feature = "icu")))]
+    #[test]
+    fn uca_identifiers_name_the_engine() {
+        assert_eq!("uca:en", Collation::default().identifier());
+        assert_eq!(
+            "uca:en/primary",
+            Collation::default()
+                .with_strength(Strength::Primary)
+                .identifier()
+        );
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn icu_identifiers_name_the_engine() {
+        assert_eq!("icu:en", Collation::default().identifier());
+        assert_eq!(
+            "icu:de/primary",
+            Collation::new("de")
+                .with_strength(Strength::Primary)
+                .identifier()
+        );
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn unlabelled_codes_are_assumed_to_be_from_icu() {
+        assert_eq!(
+            LEGACY_COLLATION_IDENTIFIER,
+            Collation::default().identifier()
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! A home-grown implementation of the Unicode Collation Algorithm
+//!
+//! This follows [UTS #10](https://www.unicode.org/reports/tr10/), using the Default Unicode
+//! Collation Element Table (DUCET) that ships alongside this file as `allkeys.txt`.  It doesn't
+//! know anything about locale-specific tailorings, so it only produces the "default" ordering,
+//! but that's a darn sight better than sorting by UTF-8 byte values, and it doesn't need ICU.
+//!
+//! The table is parsed the first time it's needed, and then kept around for the life of the
+//! process.
+//!
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization};
+
+use super::{Collation, Strength};
+use crate::Error;
+
+/// The Default Unicode Collation Element Table, as published by the Unicode Consortium
+///
+/// The only change from the upstream `allkeys.txt` is that the character name comments have been
+/// removed from each line, because they nearly double the size of the file.
+///
+const ALLKEYS: &str = include_str!("allkeys.txt");
+
+/// The parsed form of `ALLKEYS`, or the reason why it couldn't be parsed
+static TABLE: OnceLock<Result<Table, String>> = OnceLock::new();
+
+/// Locales whose ordering is the untailored DUCET ordering, which are therefore the only ones we
+/// can collate for
+const SUPPORTED_LANGUAGES: [&str; 4] = ["", "en", "root", "und"];
+
+/// A single collation element, as described in the DUCET
+///
+/// We don't do anything with the "variable" marker on elements, because we always use
+/// non-ignorable variable weighting, which treats variable elements like any other.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Element {
+    /// The weights of the element, from primary to tertiary
+    weights: [u16; 3],
+}
+
+impl Element {
+    /// Turn a DUCET collation element specification, like `[.1FA1.0020.0008]`, into an element
+    ///
+    fn parse(spec: &str) -> Result<Self, String> {
+        let weights = spec
+            .strip_prefix("[.")
+            .or_else(|| spec.strip_prefix("[*"))
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| format!("malformed collation element {spec:?}"))?
+            .split('.')
+            .map(|w| u16::from_str_radix(w, 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid weight in collation element {spec:?}: {e}"))?;
+
+        Ok(Self {
+            weights: weights.try_into().map_err(|w: Vec<u16>| {
+                format!(
+                    "wrong number of weights ({}) in collation element {spec:?}",
+                    w.len()
+                )
+            })?,
+        })
+    }
+
+    /// The weight of this element at the given level, where zero is the primary level
+    ///
+    fn weight(self, level: usize) -> u16 {
+        self.weights.get(level).copied().unwrap_or(0)
+    }
+}
+
+/// A range of codepoints whose implicit weights are explicitly specified in the DUCET
+///
+#[derive(Debug)]
+struct ImplicitRange {
+    /// The lowest codepoint in the range
+    first: u32,
+    /// The highest codepoint in the range
+    last: u32,
+    /// The base value of the primary weight of the first collation element
+    base: u16,
+}
+
+impl ImplicitRange {
+    /// Turn the details of an `@implicitweights` line, such as `17000..18AFF; FB00`, into a range
+    ///
+    fn parse(spec: &str) -> Result<Self, String> {
+        let hex = |s: &str| {
+            u32::from_str_radix(s.trim(), 16)
+                .map_err(|e| format!("invalid implicit weights {spec:?}: {e}"))
+        };
+
+        let (range, base) = spec
+            .split_once(';')
+            .ok_or_else(|| format!("malformed implicit weights {spec:?}"))?;
+        let (first, last) = range
+            .split_once("..")
+            .ok_or_else(|| format!("malformed implicit weights range {range:?}"))?;
+
+        Ok(Self {
+            first: hex(first)?,
+            last: hex(last)?,
+            base: u16::try_from(hex(base)?)
+                .map_err(|e| format!("implicit weights base out of range in {spec:?}: {e}"))?,
+        })
+    }
+}
+
+/// Everything we know about how to turn characters into collation elements
+///
+#[derive(Debug)]
+struct Table {
+    /// The collation elements for every codepoint (or sequence of codepoints) in the DUCET
+    elements: HashMap<Vec<u32>, Vec<Element>>,
+    /// How many codepoints are in the longest sequence in `elements`
+    longest: usize,
+    /// The ranges of codepoints which have implicit weights that aren't calculated the usual way
+    implicit: Vec<ImplicitRange>,
+}
+
+impl Table {
+    /// Read the contents of an `allkeys.txt` file into a table
+    ///
+    fn parse(data: &str) -> Result<Self, String> {
+        let mut elements = HashMap::new();
+        let mut longest = 0;
+        let mut implicit = vec![];
+
+        for line in data.lines() {
+            let content = line.split('#').next().unwrap_or_default().trim();
+
+            if content.is_empty() || content.starts_with("@version") {
+                continue;
+            }
+            if let Some(spec) = content.strip_prefix("@implicitweights") {
+                implicit.push(ImplicitRange::parse(spec)?);
+                continue;
+            }
+
+            let (chars, ces) = content
+                .split_once(';')
+                .ok_or_else(|| format!("malformed DUCET entry {content:?}"))?;
+            let key = chars
+                .split_whitespace()
+                .map(|c| u32::from_str_radix(c, 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("invalid codepoint in DUCET entry {content:?}: {e}"))?;
+            let elems = ces
+                .trim()
+                .split_inclusive(']')
+                .map(Element::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            longest = longest.max(key.len());
+            elements.insert(key, elems);
+        }
+
+        Ok(Self {
+            elements,
+            longest,
+            implicit,
+        })
+    }
+
+    /// Produce the collation element array for an NFD-normalised sequence of codepoints
+    ///
+    /// This is steps S2.1 through S2.4 of the UCA: repeatedly find the longest sequence at the
+    /// start of the remaining text that's in the table (including any discontiguous contractions
+    /// with later combining marks), and emit its collation elements, or generate implicit
+    /// weights for characters that aren't in the table at all.
+    ///
+    fn collation_elements(&self, mut chars: Vec<u32>) -> Vec<Element> {
+        let mut ces = vec![];
+        let mut pos = 0;
+
+        while let Some(remaining) = chars.get(pos..).filter(|r| !r.is_empty()) {
+            let found = (1..=remaining.len().min(self.longest))
+                .rev()
+                .find_map(|len| {
+                    remaining
+                        .get(..len)
+                        .and_then(|k| self.elements.get(k))
+                        .map(|e| (len, e))
+                });
+
+            let Some((len, mut elems)) = found else {
+                ces.extend(self.implicit_elements(remaining.first().copied().unwrap_or(0)));
+                pos = pos.saturating_add(1);
+                continue;
+            };
+
+            let mut key = remaining.get(..len).unwrap_or_default().to_vec();
+            let mut next = pos.saturating_add(len);
+            let mut last_skipped_ccc = 0;
+
+            while let Some(&c) = chars.get(next) {
+                let ccc = char::from_u32(c).map_or(0, canonical_combining_class);
+                if ccc == 0 {
+                    break;
+                }
+
+                key.push(c);
+                if let Some(e) = self.elements.get(&key).filter(|_| last_skipped_ccc < ccc) {
+                    elems = e;
+                    chars.remove(next);
+                } else {
+                    key.pop();
+                    last_skipped_ccc = ccc;
+                    next = next.saturating_add(1);
+                }
+            }
+
+            ces.extend(elems);
+            pos = pos.saturating_add(len);
+        }
+
+        ces
+    }
+
+    /// Derive the collation elements for a codepoint that isn't in the table
+    ///
+    /// See section 10.1 of UTS #10 for how this works.
+    ///
+    fn implicit_elements(&self, cp: u32) -> [Element; 2] {
+        let (aaaa, bbbb) = if let Some(r) = self
+            .implicit
+            .iter()
+            .find(|r| (r.first..=r.last).contains(&cp))
+        {
+            (u32::from(r.base), cp.saturating_sub(r.first))
+        } else {
+            let base = if is_core_han(cp) {
+                0xFB40
+            } else if is_other_han(cp) {
+                0xFB80
+            } else {
+                0xFBC0
+            };
+            (base | (cp >> 15u32), cp & 0x7FFF)
+        };
+
+        [
+            Element {
+                weights: [truncate(aaaa), 0x0020, 0x0002],
+            },
+            Element {
+                weights: [truncate(bbbb | 0x8000), 0, 0],
+            },
+        ]
+    }
+}
+
+/// Squish a weight calculated as a u32 into the u16 it was always destined to be
+///
+/// Implicit weight calculations can't actually produce anything larger than `0xFFFF`, but the
+/// compiler doesn't know that.
+///
+fn truncate(w: u32) -> u16 {
+    u16::try_from(w).unwrap_or(u16::MAX)
+}
+
+/// Is the codepoint a unified ideograph from the CJK Unified Ideographs or CJK Compatibility
+/// Ideographs blocks?
+///
+fn is_core_han(cp: u32) -> bool {
+    matches!(
+        cp,
+        0x4E00..=0x9FFC
+            | 0xFA0E
+            | 0xFA0F
+            | 0xFA11
+            | 0xFA13
+            | 0xFA14
+            | 0xFA1F
+            | 0xFA21
+            | 0xFA23
+            | 0xFA24
+            | 0xFA27..=0xFA29
+    )
+}
+
+/// Is the codepoint a unified ideograph from one of the CJK Unified Ideographs Extension blocks?
+///
+fn is_other_han(cp: u32) -> bool {
+    matches!(
+        cp,
+        0x3400..=0x4DBF
+            | 0x0002_0000..=0x0002_A6DD
+            | 0x0002_A700..=0x0002_B734
+            | 0x0002_B740..=0x0002_B81D
+            | 0x0002_B820..=0x0002_CEA1
+            | 0x0002_CEB0..=0x0002_EBE0
+            | 0x0003_0000..=0x0003_134A
+    )
+}
+
+/// Get the (lazily-parsed) DUCET
+///
+fn table() -> Result<&'static Table, Error> {
+    TABLE
+        .get_or_init(|| Table::parse(ALLKEYS))
+        .as_ref()
+        .map_err(|e| Error::CollationError(format!("could not load collation table: {e}")))
+}
+
+/// Make sure we're not being asked to do something we can't
+///
+fn check_locale(locale: &str) -> Result<(), Error> {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    if SUPPORTED_LANGUAGES.contains(&language.as_str()) {
+        Ok(())
+    } else {
+        Err(Error::CollationError(format!(
+            "the built-in collator does not support locale-specific ordering (asked for {locale:?})"
+        )))
+    }
+}
+
+/// Create a sort key for the given text, according to the UCA
+///
+/// The key is made up of each level's non-zero weights (as big-endian 16-bit values), with a
+/// zero weight separating each level from the next.  For the `Identical` strength, the text's
+/// NFD codepoints (as big-endian 32-bit values) are tacked on the end as a final level.
+///
+/// Since the DUCET only has three levels, the `Quaternary` strength is no different from
+/// `Tertiary` (which is also the default).
+///
+pub(crate) fn sort_key(text: &str, collation: &Collation) -> Result<Vec<u8>, Error> {
+    check_locale(collation.locale())?;
+
+    let chars = text.nfd().map(u32::from).collect::<Vec<_>>();
+    let ces = table()?.collation_elements(chars);
+
+    let levels: usize = match collation.strength() {
+        Strength::Primary => 1,
+        Strength::Secondary => 2,
+        Strength::Default | Strength::Tertiary | Strength::Quaternary | Strength::Identical => 3,
+    };
+
+    let mut key: Vec<u8> = vec![];
+
+    for level in 0..levels {
+        if level > 0 {
+            key.extend([0, 0]);
+        }
+        for w in ces.iter().map(|ce| ce.weight(level)).filter(|w| *w != 0) {
+            key.extend(w.to_be_bytes());
+        }
+    }
+
+    if collation.strength() == Strength::Identical {
+        key.extend([0, 0]);
+        for c in text.nfd() {
+            key.extend(u32::from(c).to_be_bytes());
+        }
+    }
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(text: &str) -> Vec<u8> {
+        sort_key(text, &Collation::default()).unwrap()
+    }
+
+    fn key_with_strength(text: &str, strength: Strength) -> Vec<u8> {
+        sort_key(text, &Collation::default().with_strength(strength)).unwrap()
+    }
+
+    #[test]
+    fn table_parses() {
+        let t = table().unwrap();
+
+        assert!(t.elements.len() > 30_000);
+        assert_eq!(3, t.longest);
+        assert_eq!(4, t.implicit.len());
+    }
+
+    #[test]
+    fn letters_sort_alphabetically_regardless_of_case() {
+        assert!(key("apple") < key("Banana"));
+        assert!(key("Apple") < key("banana"));
+        assert!(key("a") < key("A"));
+    }
+
+    #[test]
+    fn accented_letters_sort_with_their_base_letters() {
+        assert!(key("e") < key("\u{e9}"));
+        assert!(key("\u{e9}") < key("f"));
+        assert!(key("r\u{e9}sum\u{e9}") < key("rose"));
+    }
+
+    #[test]
+    fn normalisation_form_does_not_matter() {
+        assert_eq!(key("\u{e9}"), key("e\u{301}"));
+    }
+
+    #[test]
+    fn digits_sort_before_letters() {
+        assert!(key("9") < key("a"));
+    }
+
+    #[test]
+    fn strength_controls_what_matters() {
+        assert_eq!(
+            key_with_strength("resume", Strength::Primary),
+            key_with_strength("R\u{e9}sum\u{e9}", Strength::Primary)
+        );
+        assert_eq!(
+            key_with_strength("r\u{e9}sum\u{e9}", Strength::Secondary),
+            key_with_strength("R\u{e9}sum\u{e9}", Strength::Secondary)
+        );
+        assert_ne!(
+            key_with_strength("resume", Strength::Secondary),
+            key_with_strength("r\u{e9}sum\u{e9}", Strength::Secondary)
+        );
+        assert_ne!(
+            key_with_strength("r\u{e9}sum\u{e9}", Strength::Tertiary),
+            key_with_strength("R\u{e9}sum\u{e9}", Strength::Tertiary)
+        );
+    }
+
+    #[test]
+    fn identical_strength_notices_ignorable_characters() {
+        // Control characters are completely ignorable, so only the identical level notices them
+        assert_eq!(
+            key_with_strength("ab", Strength::Tertiary),
+            key_with_strength("a\u{1}b", Strength::Tertiary)
+        );
+        assert_ne!(
+            key_with_strength("ab", Strength::Identical),
+            key_with_strength("a\u{1}b", Strength::Identical)
+        );
+    }
+
+    #[test]
+    fn contractions_are_applied() {
+        // U+0418 U+0306 (CYRILLIC CAPITAL LETTER I + COMBINING BREVE) is a contraction in the
+        // DUCET, which sorts after every other "И"-based sequence
+        let contracted = table().unwrap().collation_elements(vec![0x0418, 0x0306]);
+        assert_eq!(1, contracted.len());
+    }
+
+    #[test]
+    fn discontiguous_contractions_are_applied() {
+        // A combining dot below (ccc 220) between the base and the breve (ccc 230) doesn't block
+        // the contraction
+        let ces = table()
+            .unwrap()
+            .collation_elements(vec![0x0418, 0x0323, 0x0306]);
+        let contracted = table().unwrap().collation_elements(vec![0x0418, 0x0306]);
+
+        assert_eq!(contracted.first(), ces.first());
+        assert_eq!(2, ces.len());
+    }
+
+    #[test]
+    fn han_characters_get_implicit_weights() {
+        let ces = table().unwrap().collation_elements(vec![0x4E00]);
+
+        assert_eq!(
+            vec![
+                Element {
+                    weights: [0xFB40, 0x0020, 0x0002]
+                },
+                Element {
+                    weights: [0xCE00, 0, 0]
+                }
+            ],
+            ces
+        );
+        assert!(key("\u{4e00}") < key("\u{3400}"));
+    }
+
+    #[test]
+    fn unassigned_codepoints_sort_last() {
+        assert!(key("\u{4e00}") < key("\u{e0080}"));
+        assert!(key("z") < key("\u{e0080}"));
+    }
+
+    #[test]
+    fn tailored_locales_are_refused() {
+        assert!(matches!(
+            sort_key("abc", &Collation::new("sv")),
+            Err(Error::CollationError(_))
+        ));
+        assert!(sort_key("abc", &Collation::new("en-AU")).is_ok());
+    }
+}
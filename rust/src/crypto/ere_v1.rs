@@ -85,8 +85,21 @@ impl<const N: usize, const W: u16> EREv1<N, W> {
         Ok(EREv1::<N, W> { ere_ciphertext: ct })
     }
 
-    /// Helps tests to make sure that `new()` isn't playing fast and loose.
-    #[cfg(test)]
+    /// Compare two ciphertexts for equality, returning an error if the comparison cannot be
+    /// performed
+    ///
+    /// As with `OREv1`, at least one of the ciphertexts needs to have a "left" part.
+    ///
+    pub(crate) fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.ere_ciphertext.has_left() || other.ere_ciphertext.has_left() {
+            Ok(self.ere_ciphertext == other.ere_ciphertext)
+        } else {
+            Err(Error::MissingLeftCiphertextError())
+        }
+    }
+
+    /// Whether this ciphertext includes the left part, which can leak information about the
+    /// plaintext
     pub(crate) fn has_left(&self) -> bool {
         self.ere_ciphertext.has_left()
     }
@@ -103,8 +116,9 @@ impl<const N: usize, const W: u16> EREv1<N, W> {
 }
 
 impl<const N: usize, const W: u16> PartialEq for EREv1<N, W> {
+    #[allow(clippy::panic)] // No way to signal an error from impl PartialEq
     fn eq(&self, other: &Self) -> bool {
-        self.ere_ciphertext == other.ere_ciphertext
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -147,4 +161,15 @@ mod tests {
             (ca == cb) == (a == b)
         }
     }
+
+    #[test]
+    fn try_eq_needs_a_left_ciphertext() {
+        let ca = EREv1::<16, 16>::new(1u64, b"test", &field()).unwrap();
+        let cb = EREv1::<16, 16>::new(1u64, b"test", &field()).unwrap();
+
+        assert!(matches!(
+            ca.try_eq(&cb),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+    }
 }
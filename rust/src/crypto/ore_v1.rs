@@ -86,8 +86,21 @@ impl<const N: usize, const W: u16> OREv1<N, W> {
         Ok(OREv1::<N, W> { ore_ciphertext: ct })
     }
 
-    /// Helps tests to make sure that `new()` isn't doing something insecure
-    #[cfg(test)]
+    /// Compare two ciphertexts, returning an error if the comparison cannot be performed
+    ///
+    /// A comparison needs at least one of the ciphertexts to have a "left" part, so if neither
+    /// does, you get a `MissingLeftCiphertextError` rather than a panic.
+    ///
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.ore_ciphertext.has_left() || other.ore_ciphertext.has_left() {
+            Ok(self.ore_ciphertext.cmp(&other.ore_ciphertext))
+        } else {
+            Err(Error::MissingLeftCiphertextError())
+        }
+    }
+
+    /// Whether this ciphertext includes the left part, which can leak information about the
+    /// plaintext
     pub(crate) fn has_left(&self) -> bool {
         self.ore_ciphertext.has_left()
     }
@@ -105,8 +118,9 @@ impl<const N: usize, const W: u16> OREv1<N, W> {
 }
 
 impl<const N: usize, const W: u16> Ord for OREv1<N, W> {
+    #[allow(clippy::panic)] // No way to signal an error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        self.ore_ciphertext.cmp(&other.ore_ciphertext)
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -217,4 +231,15 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn try_cmp_needs_a_left_ciphertext() {
+        let ca = OREv1::<8, 256>::new(1u64, b"test", &field()).unwrap();
+        let cb = OREv1::<8, 256>::new(2u64, b"test", &field()).unwrap();
+
+        assert!(matches!(
+            ca.try_cmp(&cb),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+    }
 }
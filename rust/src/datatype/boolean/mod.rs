@@ -4,13 +4,14 @@
 mod v1;
 
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
 use self::v1::V1;
 use crate::{datatype::kith::Datatype as KithDatatype, field::KeyId, Error, Field};
 
 /// An encrypted queryable boolean
 ///
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
 #[non_exhaustive]
 pub enum Boolean {
@@ -73,6 +74,31 @@ impl Boolean {
         }
     }
 
+    /// Compare this boolean with another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the booleans were encrypted with different field keys, if either has
+    /// been made unqueryable, if neither has the left ciphertext required to perform the
+    /// comparison, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Boolean::v1(s), Boolean::v1(o)) => s.try_cmp(o),
+            (Boolean::Unknown, _) | (_, Boolean::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this boolean is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`Boolean::try_cmp()`](Boolean::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
     /// Remove the ability to perform any queries on this boolean
     ///
     /// Sometimes you just want to be able to store a safely encrypted boolean, without any ability
@@ -94,6 +120,27 @@ impl Boolean {
     }
 }
 
+impl Ord for Boolean {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Boolean {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Boolean {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Boolean {}
+
 impl KithDatatype for Boolean {
     fn key_id(&self) -> KeyId {
         match self {
@@ -99,26 +99,28 @@ impl V1 {
     pub(crate) fn make_unqueryable(&mut self) {
         self.ore_ciphertext = None;
     }
+
+    /// Compare two ciphertexts, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
 }
 
 impl Ord for V1 {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to signal error from impl Ord
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        assert!(
-            self.kid == other.kid,
-            "Cannot compare ciphertexts from different keys"
-        );
-
-        let lhs = self
-            .ore_ciphertext
-            .as_ref()
-            .expect("Cannot compare without an ORE ciphertext on the left-hand side");
-        let rhs = other
-            .ore_ciphertext
-            .as_ref()
-            .expect("Cannot compare without an ORE ciphertext on the right-hand side");
-
-        lhs.cmp(rhs)
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -0,0 +1,233 @@
+//! A date that is known to exist
+//!
+
+use crate::Error;
+
+/// A valid date in the proleptic Gregorian calendar
+///
+/// This is what [`Date::new()`](crate::datatype::Date::new) and friends actually encrypt.  You
+/// don't usually need to create one yourself, because anything which can be converted into a
+/// `CalendarDate` -- such as a `(year, month, day)` tuple, or, with the `chrono` or `time`
+/// features enabled, a `chrono::NaiveDate` or `time::Date` -- can be passed instead.  Likewise,
+/// [`Date::decrypt_as()`](crate::datatype::Date::decrypt_as) can produce anything that a
+/// `CalendarDate` can be converted into.
+///
+/// The proleptic Gregorian calendar is the Gregorian calendar extended backwards to before it
+/// was introduced, with a year zero, so that 1 BCE is year `0`, 2 BCE is year `-1`, and so on.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CalendarDate {
+    /// The year
+    year: i16,
+    /// The month of the year, starting from 1
+    month: u8,
+    /// The day of the month, starting from 1
+    day: u8,
+}
+
+impl CalendarDate {
+    /// Make a new date, after checking that it actually exists
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the month is not between 1 and 12, or if
+    /// the day does not exist in that month (taking leap years into account).
+    ///
+    pub fn new(year: i16, month: u8, day: u8) -> Result<Self, Error> {
+        let days = Self::days_in_month(year, month).ok_or_else(|| {
+            Error::RangeError(format!("{month} is not a valid month (must be 1-12)"))
+        })?;
+
+        if day == 0 || day > days {
+            return Err(Error::RangeError(format!(
+                "{year:04}-{month:02} does not have a day {day}"
+            )));
+        }
+
+        Ok(Self { year, month, day })
+    }
+
+    /// The year
+    ///
+    #[must_use]
+    pub fn year(&self) -> i16 {
+        self.year
+    }
+
+    /// The month of the year, from 1 (January) to 12 (December)
+    ///
+    #[must_use]
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// The day of the month, starting from 1
+    ///
+    #[must_use]
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Whether the given year is a leap year
+    ///
+    fn is_leap_year(year: i16) -> bool {
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    /// How many days there are in the given month, or `None` if the month doesn't exist
+    ///
+    fn days_in_month(year: i16, month: u8) -> Option<u8> {
+        match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => Some(31),
+            4 | 6 | 9 | 11 => Some(30),
+            2 if Self::is_leap_year(year) => Some(29),
+            2 => Some(28),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<(i16, u8, u8)> for CalendarDate {
+    type Error = Error;
+
+    fn try_from(date: (i16, u8, u8)) -> Result<Self, Error> {
+        let (y, m, d) = date;
+        Self::new(y, m, d)
+    }
+}
+
+impl From<CalendarDate> for (i16, u8, u8) {
+    fn from(date: CalendarDate) -> Self {
+        (date.year, date.month, date.day)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDate> for CalendarDate {
+    type Error = Error;
+
+    fn try_from(date: chrono::NaiveDate) -> Result<Self, Error> {
+        use chrono::Datelike;
+
+        let year = i16::try_from(date.year()).map_err(|e| {
+            Error::RangeError(format!("year {} is out of range ({e})", date.year()))
+        })?;
+        let month = u8::try_from(date.month())
+            .map_err(|e| Error::RangeError(format!("invalid month ({e})")))?;
+        let day = u8::try_from(date.day())
+            .map_err(|e| Error::RangeError(format!("invalid day ({e})")))?;
+
+        Self::new(year, month, day)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<CalendarDate> for chrono::NaiveDate {
+    type Error = Error;
+
+    fn try_from(date: CalendarDate) -> Result<Self, Error> {
+        chrono::NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+            .ok_or_else(|| Error::RangeError(format!("{date:?} cannot be represented by chrono")))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::Date> for CalendarDate {
+    type Error = Error;
+
+    fn try_from(date: time::Date) -> Result<Self, Error> {
+        let year = i16::try_from(date.year()).map_err(|e| {
+            Error::RangeError(format!("year {} is out of range ({e})", date.year()))
+        })?;
+
+        Self::new(year, date.month().into(), date.day())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<CalendarDate> for time::Date {
+    type Error = Error;
+
+    fn try_from(date: CalendarDate) -> Result<Self, Error> {
+        let month = time::Month::try_from(date.month)
+            .map_err(|e| Error::RangeError(format!("invalid month ({e})")))?;
+
+        time::Date::from_calendar_date(date.year.into(), month, date.day)
+            .map_err(|e| Error::RangeError(format!("{date:?} cannot be represented by time ({e})")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_dates_are_accepted() {
+        for (y, m, d) in [
+            (1970, 1, 1),
+            (2023, 12, 31),
+            (2024, 2, 29),
+            (2000, 2, 29),
+            (0, 2, 29),
+            (-4, 2, 29),
+            (i16::MIN, 1, 1),
+            (i16::MAX, 12, 31),
+        ] {
+            assert!(CalendarDate::new(y, m, d).is_ok(), "{y}-{m}-{d}");
+        }
+    }
+
+    #[test]
+    fn imaginary_dates_are_rejected() {
+        for (y, m, d) in [
+            (2023, 2, 31),
+            (2023, 13, 1),
+            (2023, 0, 1),
+            (2023, 1, 0),
+            (2023, 4, 31),
+            (2023, 2, 29),
+            (1900, 2, 29),
+            (-1, 2, 29),
+        ] {
+            assert!(
+                matches!(CalendarDate::new(y, m, d), Err(Error::RangeError(_))),
+                "{y}-{m}-{d}"
+            );
+        }
+    }
+
+    #[test]
+    fn tuples_convert_both_ways() {
+        let date = CalendarDate::try_from((2024, 2, 29)).unwrap();
+
+        assert_eq!((2024, 2, 29), <(i16, u8, u8)>::from(date));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_dates_convert_both_ways() {
+        let naive = chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let date = CalendarDate::try_from(naive).unwrap();
+
+        assert_eq!((2024, 2, 29), <(i16, u8, u8)>::from(date));
+        assert_eq!(naive, chrono::NaiveDate::try_from(date).unwrap());
+        assert!(matches!(
+            CalendarDate::try_from(chrono::NaiveDate::from_ymd_opt(40000, 1, 1).unwrap()),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_dates_convert_both_ways() {
+        let t = time::Date::from_calendar_date(2024, time::Month::February, 29).unwrap();
+        let date = CalendarDate::try_from(t).unwrap();
+
+        assert_eq!((2024, 2, 29), <(i16, u8, u8)>::from(date));
+        assert_eq!(t, time::Date::try_from(date).unwrap());
+        assert!(matches!(
+            time::Date::try_from(CalendarDate::new(i16::MAX, 1, 1).unwrap()),
+            Err(Error::RangeError(_))
+        ));
+    }
+}
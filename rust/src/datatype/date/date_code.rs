@@ -0,0 +1,108 @@
+//! Encrypted date codes, for range-querying dates
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::{
+    crypto::OREv1,
+    datatype::kith::{Datatype as KithDatatype, Member as KithMember},
+    field::KeyId,
+    Error,
+};
+
+/// Versioning support
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[allow(clippy::large_enum_variant, clippy::missing_docs_in_private_items)] // Unknown is only a placeholder
+enum Ciphertext {
+    #[allow(non_camel_case_types)]
+    v1 {
+        #[serde(rename = "y")]
+        year: OREv1<2, 256>,
+        #[serde(rename = "m")]
+        month: OREv1<1, 32>,
+        #[serde(rename = "d")]
+        day: OREv1<1, 32>,
+    },
+    Unknown,
+}
+
+/// The encrypted year, month, and day of a date, for comparing against stored dates
+///
+/// This is what you get from [`Date::query()`](crate::datatype::Date::query), to compare
+/// against stored dates with [`Date::try_cmp_query()`](crate::datatype::Date::try_cmp_query).
+///
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DateCode {
+    /// The ORE ciphertexts of the year, month, and day
+    #[serde(rename = "o")]
+    ore_ciphertexts: Ciphertext,
+
+    /// The field key ID which was used to generate this ciphertext
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+impl DateCode {
+    /// Create a date code from the `OREv1` ciphertexts of a date's components
+    ///
+    pub(crate) fn from_ore_v1(
+        parts: (OREv1<2, 256>, OREv1<1, 32>, OREv1<1, 32>),
+        key_id: KeyId,
+    ) -> Self {
+        let (year, month, day) = parts;
+
+        Self {
+            ore_ciphertexts: Ciphertext::v1 { year, month, day },
+            kid: key_id.into(),
+        }
+    }
+
+    /// Compare this date code with another
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the date codes were encrypted with different field keys, if neither
+    /// date code contains left ciphertexts, or if either date code is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        match (&self.ore_ciphertexts, &other.ore_ciphertexts) {
+            (
+                Ciphertext::v1 {
+                    year: ly,
+                    month: lm,
+                    day: ld,
+                },
+                Ciphertext::v1 {
+                    year: ry,
+                    month: rm,
+                    day: rd,
+                },
+            ) => Ok(ly.try_cmp(ry)?.then(lm.try_cmp(rm)?).then(ld.try_cmp(rd)?)),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
+}
+
+impl KithMember for DateCode {}
+
+impl KithDatatype for DateCode {
+    fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self.ore_ciphertexts {
+            Ciphertext::v1 { .. } => 1,
+            Ciphertext::Unknown => 0,
+        }
+    }
+}
@@ -1,42 +1,65 @@
 //! Store and query dates in an encrypted form
 //!
 
+mod calendar_date;
+mod date_code;
 mod v1;
+mod v2;
 
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
-use self::v1::V1;
-use crate::{datatype::kith::Datatype as KithDatatype, field::KeyId, Error, Field};
+pub use self::{calendar_date::CalendarDate, date_code::DateCode};
+
+use self::{v1::V1, v2::V2};
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
 
 /// The encrypted, queryable date
 ///
-#[derive(Debug, Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
 #[non_exhaustive]
 pub enum Date {
     #[allow(non_camel_case_types)]
     v1(Box<V1>),
+    #[allow(non_camel_case_types)]
+    v2(Box<V2>),
     Unknown,
 }
 
 impl Date {
     /// Create a new encrypted, queryable date
     ///
-    /// The date is represented as `(y, m, d)` in the tuple.
+    /// The date can be anything that converts into a [`CalendarDate`], such as a `(y, m, d)`
+    /// tuple, or (with the appropriate features enabled) a `chrono::NaiveDate` or `time::Date`.
     ///
     #[doc = include_str!("../encryption_contexts.md")]
     ///
     /// # Errors
     ///
-    /// Can return an error if the process of encrypting the data fails.
+    /// Returns a [`RangeError`](Error::RangeError) if the date does not exist in the proleptic
+    /// Gregorian calendar (such as February 31st), and can return an error if the process of
+    /// encrypting the data fails.
     ///
-    pub fn new(date: (i16, u8, u8), context: &[u8], field: &Field) -> Result<Date, Error> {
-        Ok(Date::v1(Box::new(V1::new(date, context, field)?)))
+    pub fn new<D>(date: D, context: &[u8], field: &Field) -> Result<Date, Error>
+    where
+        D: TryInto<CalendarDate>,
+        Error: From<D::Error>,
+    {
+        let date: CalendarDate = date.try_into()?;
+
+        Ok(Date::v2(Box::new(V2::new(date.into(), context, field)?)))
     }
 
     /// Create a new encrypted, queryable date with degraded security
     ///
-    /// The date is represented as `(y, m, d)` in the tuple.
+    /// The date can be anything that converts into a [`CalendarDate`], as per
+    /// [`Date::new()`](Date::new).
     ///
     /// While the date itself is securely encrypted, the ciphertexts produced by this function may
     /// contain components that allow an attacker to infer the plaintext or some part(s) thereof.
@@ -47,15 +70,20 @@ impl Date {
     ///
     /// # Errors
     ///
-    /// Can return an error if the process of encrypting the data fails.
+    /// Returns a [`RangeError`](Error::RangeError) if the date does not exist, and can return an
+    /// error if the process of encrypting the data fails.
     ///
-    pub fn new_with_unsafe_parts(
-        date: (i16, u8, u8),
-        context: &[u8],
-        field: &Field,
-    ) -> Result<Date, Error> {
-        Ok(Date::v1(Box::new(V1::new_with_unsafe_parts(
-            date, context, field,
+    pub fn new_with_unsafe_parts<D>(date: D, context: &[u8], field: &Field) -> Result<Date, Error>
+    where
+        D: TryInto<CalendarDate>,
+        Error: From<D::Error>,
+    {
+        let date: CalendarDate = date.try_into()?;
+
+        Ok(Date::v2(Box::new(V2::new_with_unsafe_parts(
+            date.into(),
+            context,
+            field,
         )?)))
     }
 
@@ -70,6 +98,273 @@ impl Date {
     pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<(i16, u8, u8), Error> {
         match self {
             Date::v1(d) => d.decrypt(context, field),
+            Date::v2(d) => d.decrypt(context, field),
+            Date::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the date, and convert it into whatever type you like
+    ///
+    /// Anything that a [`CalendarDate`] can be converted into will do, such as a `(y, m, d)`
+    /// tuple, or (with the appropriate features enabled) a `chrono::NaiveDate` or `time::Date`.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error in all the same situations as [`Date::decrypt()`](Date::decrypt), as
+    /// well as if the decrypted date is not a valid date (which can only happen for dates
+    /// encrypted before dates were validated), or if it cannot be represented by the requested
+    /// type.
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: TryFrom<CalendarDate>,
+        Error: From<T::Error>,
+    {
+        let date = CalendarDate::try_from(self.decrypt(context, field)?)?;
+
+        Ok(T::try_from(date)?)
+    }
+
+    /// Compare this date with another, without panicking if it can't be done
+    ///
+    /// Comparing dates via `Ord` will panic if (say) one of them has been made unqueryable, or
+    /// they came from different fields.  If you'd rather find out about that via an error, this is
+    /// the method for you.
+    ///
+    /// Dates of different versions are encrypted with different keys, and so can't be compared;
+    /// [upgrade](Date::upgrade) the older one first.  Bear in mind that version 1 dates can only
+    /// be compared with other version 1 dates that were encrypted with the same context, and
+    /// there's no way to tell if they weren't -- the comparison just gives a meaningless answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dates were encrypted with different field keys, if either has been
+    /// made unqueryable, if neither has the left ciphertexts required to perform the comparison,
+    /// if the dates are of different versions, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Date::v1(s), Date::v1(o)) => s.try_cmp(o),
+            (Date::v2(s), Date::v2(o)) => s.try_cmp(o),
+            (Date::Unknown, _) | (_, Date::Unknown) => Err(Error::UnknownVersionError()),
+            (s, o) => Err(Error::VersionMismatchError(format!(
+                "v{} vs v{}",
+                s.ciphertext_version(),
+                o.ciphertext_version()
+            ))),
+        }
+    }
+
+    /// Determine whether this date is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`Date::try_cmp()`](Date::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored dates
+    ///
+    /// This is what you need for range queries, like `WHERE dob BETWEEN '1970-01-01' AND
+    /// '1979-12-31'`.  The date can be anything that converts into a [`CalendarDate`], and is
+    /// encrypted with the field's ORE keys, so the result is suitable for passing to
+    /// [`Date::try_cmp_query()`](Date::try_cmp_query) for any date encrypted using the same
+    /// field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the date does not exist, and can return an
+    /// error if the encryption process fails.
+    ///
+    pub fn query<D>(date: D, field: &Field) -> Result<Kith<DateCode>, Error>
+    where
+        D: TryInto<CalendarDate>,
+        Error: From<D::Error>,
+    {
+        let date: CalendarDate = date.try_into()?;
+
+        let mut k = Kith::new();
+        k.add_member(DateCode::from_ore_v1(
+            V2::ore_query(date.into(), field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare this date with the date represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by [`Date::query()`](Date::query), using
+    /// the same field as this date.  The result says where this date sorts relative to the
+    /// queried date, so `Ordering::Greater` means that this date is after the queried one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the date has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this date, or if the date is a version 1 value (which
+    /// can't be queried, because its ORE keys depend on its encryption context) or of an unknown
+    /// version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<DateCode>) -> Result<Ordering, Error> {
+        let code = match self {
+            Date::v1(_) => {
+                return Err(Error::VersionMismatchError(
+                    "v1 dates cannot be queried, upgrade them first".to_string(),
+                ))
+            }
+            Date::v2(d) => d.ore_parts().ok().map(|(y, m, day)| {
+                DateCode::from_ore_v1((y.clone(), m.clone(), day.clone()), d.key_id())
+            }),
+            Date::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(code, DateCode::try_cmp)
+    }
+
+    /// Get the encrypted year of the date
+    ///
+    /// This is returned as an orderable encrypted ciphertext, so you can search for dates in a
+    /// particular year (or range of years) by comparing it against the values produced by
+    /// [`Date::query_year()`](Date::query_year).
+    ///
+    /// If the date has been made unqueryable, or is a version 1 date (whose components are
+    /// encrypted with keys that depend on its encryption context, and so can't be queried), this
+    /// method will return `None`.
+    ///
+    #[must_use]
+    pub fn year(&self) -> Option<ORE<2, 256>> {
+        match self {
+            Date::v2(d) => d
+                .ore_parts()
+                .ok()
+                .map(|(y, _, _)| ORE::from_ore_v1(y.clone(), d.key_id())),
+            Date::v1(_) | Date::Unknown => None,
+        }
+    }
+
+    /// Get the encrypted month-of-the-year of the date
+    ///
+    /// As per [`Date::year()`](Date::year), but for comparing against the values produced by
+    /// [`Date::query_month()`](Date::query_month), for queries like "all birthdays in March".
+    ///
+    #[must_use]
+    pub fn month(&self) -> Option<ORE<1, 32>> {
+        match self {
+            Date::v2(d) => d
+                .ore_parts()
+                .ok()
+                .map(|(_, m, _)| ORE::from_ore_v1(m.clone(), d.key_id())),
+            Date::v1(_) | Date::Unknown => None,
+        }
+    }
+
+    /// Get the encrypted day-of-the-month of the date
+    ///
+    /// As per [`Date::year()`](Date::year), but for comparing against the values produced by
+    /// [`Date::query_day()`](Date::query_day).
+    ///
+    #[must_use]
+    pub fn day(&self) -> Option<ORE<1, 32>> {
+        match self {
+            Date::v2(d) => d
+                .ore_parts()
+                .ok()
+                .map(|(_, _, day)| ORE::from_ore_v1(day.clone(), d.key_id())),
+            Date::v1(_) | Date::Unknown => None,
+        }
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the years of dates
+    ///
+    /// The result can be compared against the value returned by [`Date::year()`](Date::year) for
+    /// any date encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query_year(year: i16, field: &Field) -> Result<Kith<ORE<2, 256>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V2::ore_query_year(year, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the months of dates
+    ///
+    /// The result can be compared against the value returned by [`Date::month()`](Date::month)
+    /// for any date encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the month is not between 1 and 12, and can
+    /// return an error if the encryption process fails.
+    ///
+    pub fn query_month(month: u8, field: &Field) -> Result<Kith<ORE<1, 32>>, Error> {
+        if !(1..=12).contains(&month) {
+            return Err(Error::RangeError(format!(
+                "{month} is not a valid month (must be 1-12)"
+            )));
+        }
+
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V2::ore_query_month(month, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the days of dates
+    ///
+    /// The result can be compared against the value returned by [`Date::day()`](Date::day) for
+    /// any date encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the day is not between 1 and 31, and can
+    /// return an error if the encryption process fails.
+    ///
+    pub fn query_day(day: u8, field: &Field) -> Result<Kith<ORE<1, 32>>, Error> {
+        if !(1..=31).contains(&day) {
+            return Err(Error::RangeError(format!(
+                "{day} is not a valid day of the month (must be 1-31)"
+            )));
+        }
+
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V2::ore_query_day(day, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Convert this date to the latest version
+    ///
+    /// Older versions of `Date` remain readable, but new dates are always created with the latest
+    /// version, and dates of different versions can't be compared against each other.  Upgrading
+    /// re-encrypts the date, so the `context` must be the same one that was used when it was
+    /// created.  The upgraded date only has unsafe parts if the original did, and unqueryable
+    /// dates stay unqueryable.
+    ///
+    /// Dates which are already the latest version are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the date could not be decrypted, if the encryption process fails,
+    /// or if the date is of an unknown version.
+    ///
+    pub fn upgrade(&mut self, context: &[u8], field: &Field) -> Result<(), Error> {
+        match self {
+            Date::v1(d) => {
+                *self = Date::v2(Box::new(V2::from_v1(d, context, field)?));
+                Ok(())
+            }
+            Date::v2(_) => Ok(()),
             Date::Unknown => Err(Error::UnknownVersionError()),
         }
     }
@@ -91,15 +386,41 @@ impl Date {
                 d.make_unqueryable();
                 Ok(())
             }
+            Date::v2(d) => {
+                d.make_unqueryable();
+                Ok(())
+            }
             Date::Unknown => Err(Error::UnknownVersionError()),
         }
     }
 }
 
+impl Ord for Date {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Date {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Date {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Date {}
+
 impl KithDatatype for Date {
     fn key_id(&self) -> KeyId {
         match self {
             Date::v1(t) => t.key_id(),
+            Date::v2(t) => t.key_id(),
             Date::Unknown => Default::default(),
         }
     }
@@ -107,7 +428,205 @@ impl KithDatatype for Date {
     fn ciphertext_version(&self) -> u32 {
         match self {
             Date::v1(_) => 1,
+            Date::v2(_) => 2,
             Date::Unknown => 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn range_query() {
+        let dob = Date::new((1975, 6, 15), b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            dob.try_cmp_query(&Date::query((1970, 1, 1), &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            dob.try_cmp_query(&Date::query((1975, 6, 15), &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            dob.try_cmp_query(&Date::query((1979, 12, 31), &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn v1_dates_cannot_be_queried() {
+        let dob = Date::v1(Box::new(
+            V1::new((1975, 6, 15), b"row 1", &field()).unwrap(),
+        ));
+
+        assert!(matches!(
+            dob.try_cmp_query(&Date::query((1970, 1, 1), &field()).unwrap()),
+            Err(Error::VersionMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn unqueryable_dates_cannot_be_queried() {
+        let mut dob = Date::new((1975, 6, 15), b"row 1", &field()).unwrap();
+        dob.make_unqueryable().unwrap();
+
+        assert!(matches!(
+            dob.try_cmp_query(&Date::query((1970, 1, 1), &field()).unwrap()),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+
+    #[test]
+    fn mixed_versions_cannot_be_compared() {
+        let v1 = Date::v1(Box::new(
+            V1::new_with_unsafe_parts((1975, 6, 15), b"row 1", &field()).unwrap(),
+        ));
+        let v2 = Date::new_with_unsafe_parts((1975, 6, 15), b"row 1", &field()).unwrap();
+
+        assert!(matches!(
+            v1.try_cmp(&v2),
+            Err(Error::VersionMismatchError(_))
+        ));
+    }
+
+    fn component_matches<const N: usize, const W: u16>(
+        component: Option<ORE<N, W>>,
+        query: &Kith<ORE<N, W>>,
+    ) -> Ordering {
+        let c = component.unwrap();
+
+        query
+            .compatible_member(&c)
+            .unwrap()
+            .try_cmp(&c)
+            .unwrap()
+            .reverse()
+    }
+
+    #[test]
+    fn component_queries() {
+        let dob = Date::new((1975, 3, 15), b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Equal,
+            component_matches(dob.month(), &Date::query_month(3, &field()).unwrap())
+        );
+        assert_eq!(
+            Ordering::Less,
+            component_matches(dob.month(), &Date::query_month(4, &field()).unwrap())
+        );
+        assert_eq!(
+            Ordering::Greater,
+            component_matches(dob.year(), &Date::query_year(1970, &field()).unwrap())
+        );
+        assert_eq!(
+            Ordering::Less,
+            component_matches(dob.day(), &Date::query_day(31, &field()).unwrap())
+        );
+    }
+
+    #[test]
+    fn v1_and_unqueryable_dates_have_no_components() {
+        let old = Date::v1(Box::new(
+            V1::new((1975, 3, 15), b"row 1", &field()).unwrap(),
+        ));
+        let mut unqueryable = Date::new((1975, 3, 15), b"row 1", &field()).unwrap();
+        unqueryable.make_unqueryable().unwrap();
+
+        for d in [old, unqueryable] {
+            assert!(d.year().is_none());
+            assert!(d.month().is_none());
+            assert!(d.day().is_none());
+        }
+    }
+
+    #[test]
+    fn imaginary_dates_are_rejected() {
+        for date in [(2023, 2, 31), (2023, 13, 1), (2023, 1, 0), (1900, 2, 29)] {
+            assert!(matches!(
+                Date::new(date, b"row 1", &field()),
+                Err(Error::RangeError(_))
+            ));
+            assert!(matches!(
+                Date::new_with_unsafe_parts(date, b"row 1", &field()),
+                Err(Error::RangeError(_))
+            ));
+            assert!(matches!(
+                Date::query(date, &field()),
+                Err(Error::RangeError(_))
+            ));
+        }
+
+        assert!(matches!(
+            Date::query_month(13, &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            Date::query_day(0, &field()),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn decrypt_as_calendar_date() {
+        let leap_day = Date::new((2000, 2, 29), b"row 1", &field()).unwrap();
+        let d: CalendarDate = leap_day.decrypt_as(b"row 1", &field()).unwrap();
+
+        assert_eq!((2000, 2, 29), (d.year(), d.month(), d.day()));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_round_trip() {
+        let naive = chrono::NaiveDate::from_ymd_opt(1975, 6, 15).unwrap();
+        let dob = Date::new(naive, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            naive,
+            dob.decrypt_as::<chrono::NaiveDate>(b"row 1", &field())
+                .unwrap()
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_round_trip() {
+        let t = time::Date::from_calendar_date(1975, time::Month::June, 15).unwrap();
+        let dob = Date::new(t, b"row 1", &field()).unwrap();
+
+        assert_eq!(t, dob.decrypt_as::<time::Date>(b"row 1", &field()).unwrap());
+    }
+
+    #[test]
+    fn upgraded_dates_can_be_compared_across_contexts() {
+        let mut old = Date::v1(Box::new(
+            V1::new((1975, 6, 15), b"row 1", &field()).unwrap(),
+        ));
+        old.upgrade(b"row 1", &field()).unwrap();
+
+        assert_eq!(2, old.ciphertext_version());
+        assert_eq!((1975, 6, 15), old.decrypt(b"row 1", &field()).unwrap());
+        assert_eq!(
+            Ordering::Less,
+            old.try_cmp(&Date::new_with_unsafe_parts((1975, 6, 16), b"row 2", &field()).unwrap())
+                .unwrap()
+        );
+    }
+}
@@ -50,24 +50,36 @@ struct StoredDate {
 const I16_OFFSET: i32 = 0x8000;
 
 impl V1 {
+    // New dates are always created as the latest version, so these constructors only exist so
+    // that the tests can get their hands on a complete V1 value.
+
     /// Encrypt the date
+    #[cfg(test)]
     pub(crate) fn new(date: (i16, u8, u8), context: &[u8], field: &Field) -> Result<V1, Error> {
-        Self::encrypt(date, context, field, false)
+        Self::encrypt(date, context, context, field, false)
     }
 
     /// Encrypt the date in a degraded security form
+    #[cfg(test)]
     pub(crate) fn new_with_unsafe_parts(
         date: (i16, u8, u8),
         context: &[u8],
         field: &Field,
     ) -> Result<V1, Error> {
-        Self::encrypt(date, context, field, true)
+        Self::encrypt(date, context, context, field, true)
     }
 
     /// Do the hard yards of actually producing ciphertexts and constructing the struct
-    fn encrypt(
+    ///
+    /// The identifiers of the ORE subkeys for the year, month, and day are made by sticking
+    /// `.year`, `.month`, and `.day` onto the end of `ore_subkey_prefix`.  Version 1 values use
+    /// the encryption context as the prefix, which means that only values encrypted with the same
+    /// context can be compared.  Later versions pass in something else.
+    ///
+    pub(crate) fn encrypt(
         date: (i16, u8, u8),
         context: &[u8],
+        ore_subkey_prefix: &[u8],
         field: &Field,
         include_left: bool,
     ) -> Result<V1, Error> {
@@ -84,6 +96,44 @@ impl V1 {
 
         let aes = AES256v1::new(&msg, context, field)?;
 
+        let (ore_year, ore_month, ore_day) =
+            Self::ore(date, ore_subkey_prefix, field, include_left)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            year_ciphertext: Some(ore_year),
+            month_ciphertext: Some(ore_month),
+            day_ciphertext: Some(ore_day),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt the year, month, and day of a date into order-revealing ciphertexts, using the
+    /// field subkeys named by `subkey_prefix`
+    ///
+    pub(crate) fn ore(
+        date: (i16, u8, u8),
+        subkey_prefix: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<(OREv1<2, 256>, OREv1<1, 32>, OREv1<1, 32>), Error> {
+        let (y, m, d) = date;
+
+        Ok((
+            Self::ore_year(y, subkey_prefix, field, include_left)?,
+            Self::ore_month(m, subkey_prefix, field, include_left)?,
+            Self::ore_day(d, subkey_prefix, field, include_left)?,
+        ))
+    }
+
+    /// Encrypt a year into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_year(
+        y: i16,
+        subkey_prefix: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<2, 256>, Error> {
         let uy: u16 = check_overflow(
             i32::from(y).overflowing_add(I16_OFFSET),
             "while translating DateV1 year to u16",
@@ -93,36 +143,55 @@ impl V1 {
             Error::EncodingError(format!("failed to convert i16 year {y} to u16 ({e})"))
         })?;
 
-        let mut year_context = context.to_vec();
-        year_context.extend_from_slice(b".year");
-        let mut month_context = context.to_vec();
-        month_context.extend_from_slice(b".month");
-        let mut day_context = context.to_vec();
-        day_context.extend_from_slice(b".day");
+        let year_context = Self::subkey_id(subkey_prefix, b".year");
 
-        let ore_year = if include_left {
-            OREv1::<2, 256>::new_with_left(uy, &year_context, field)?
+        if include_left {
+            Ok(OREv1::<2, 256>::new_with_left(uy, &year_context, field)?)
         } else {
-            OREv1::<2, 256>::new(uy, &year_context, field)?
-        };
-        let ore_month = if include_left {
-            OREv1::<1, 32>::new_with_left(m, &month_context, field)?
+            Ok(OREv1::<2, 256>::new(uy, &year_context, field)?)
+        }
+    }
+
+    /// Encrypt a month-of-the-year into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_month(
+        m: u8,
+        subkey_prefix: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<1, 32>, Error> {
+        let month_context = Self::subkey_id(subkey_prefix, b".month");
+
+        if include_left {
+            Ok(OREv1::<1, 32>::new_with_left(m, &month_context, field)?)
         } else {
-            OREv1::<1, 32>::new(m, &month_context, field)?
-        };
-        let ore_day = if include_left {
-            OREv1::<1, 32>::new_with_left(d, &day_context, field)?
+            Ok(OREv1::<1, 32>::new(m, &month_context, field)?)
+        }
+    }
+
+    /// Encrypt a day-of-the-month into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_day(
+        d: u8,
+        subkey_prefix: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<1, 32>, Error> {
+        let day_context = Self::subkey_id(subkey_prefix, b".day");
+
+        if include_left {
+            Ok(OREv1::<1, 32>::new_with_left(d, &day_context, field)?)
         } else {
-            OREv1::<1, 32>::new(d, &day_context, field)?
-        };
+            Ok(OREv1::<1, 32>::new(d, &day_context, field)?)
+        }
+    }
 
-        Ok(V1 {
-            aes_ciphertext: aes,
-            year_ciphertext: Some(ore_year),
-            month_ciphertext: Some(ore_month),
-            day_ciphertext: Some(ore_day),
-            kid: field.key_id()?.into(),
-        })
+    /// Glue a prefix and a component name together into a subkey identifier
+    ///
+    fn subkey_id(prefix: &[u8], component: &[u8]) -> Vec<u8> {
+        let mut id = prefix.to_vec();
+        id.extend_from_slice(component);
+        id
     }
 
     /// Turn the ciphertext back into a date, or at least a tuple representing a date
@@ -150,39 +219,40 @@ impl V1 {
         self.day_ciphertext = None;
     }
 
-    /// Create a sortable structure of the various encrypted y/m/d components, for comparison
-    /// purposes
-    #[allow(clippy::expect_used)] // This is only used in impl Ord, which can't return an error
-    fn ore_parts(&self) -> (&OREv1<2, 256>, &OREv1<1, 32>, &OREv1<1, 32>) {
-        let y = self
-            .year_ciphertext
-            .as_ref()
-            .expect("Cannot extract 'year' from ciphertext");
-        let m = self
-            .month_ciphertext
-            .as_ref()
-            .expect("Cannot extract 'month' from ciphertext");
-        let d = self
-            .day_ciphertext
-            .as_ref()
-            .expect("Cannot extract 'day' from ciphertext");
-
-        (y, m, d)
+    /// Extract the various encrypted y/m/d components, for comparison purposes
+    pub(crate) fn ore_parts(
+        &self,
+    ) -> Result<(&OREv1<2, 256>, &OREv1<1, 32>, &OREv1<1, 32>), Error> {
+        let y = self.year_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("cannot extract 'year' from ciphertext".to_string())
+        })?;
+        let m = self.month_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("cannot extract 'month' from ciphertext".to_string())
+        })?;
+        let d = self.day_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("cannot extract 'day' from ciphertext".to_string())
+        })?;
+
+        Ok((y, m, d))
+    }
+
+    /// Compare two dates, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let (ly, lm, ld) = self.ore_parts()?;
+        let (ry, rm, rd) = other.ore_parts()?;
+
+        Ok(ly.try_cmp(ry)?.then(lm.try_cmp(rm)?).then(ld.try_cmp(rd)?))
     }
 }
 
 impl Ord for V1 {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to signal error from impl Ord
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        assert!(
-            self.kid == other.kid,
-            "Cannot compare ciphertexts from different keys"
-        );
-
-        let lhs = self.ore_parts();
-        let rhs = other.ore_parts();
-
-        lhs.cmp(&rhs)
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -248,4 +318,16 @@ mod tests {
         assert!(!value.month_ciphertext.unwrap().has_left());
         assert!(!value.day_ciphertext.unwrap().has_left());
     }
+
+    #[test]
+    fn unqueryable_dates_cannot_be_compared() {
+        let value = V1::new_with_unsafe_parts((1970, 1, 1), b"somecontext", &field()).unwrap();
+        let mut unqueryable = V1::new((1970, 1, 1), b"somecontext", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
 }
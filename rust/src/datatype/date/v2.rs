@@ -0,0 +1,208 @@
+//! Version 2 of the Date datatype
+//!
+//! Version 1 used the encryption context to derive the ORE keys for the year, month, and day,
+//! which made it impossible to compare dates that were encrypted with different contexts.
+//! Version 2 derives them from a fixed, field-scoped identifier instead, and is otherwise
+//! identical.
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::v1::V1;
+use crate::{crypto::OREv1, field::KeyId, Error, Field};
+
+/// The ciphertext and all its components
+///
+/// The serialised form is exactly the same as version 1; it's only the keys used for the ORE
+/// ciphertexts that differ.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+#[doc(hidden)]
+pub struct V2 {
+    /// The ciphertexts, which are the same shape as they ever were
+    base: V1,
+}
+
+/// Prefix of the identifiers for the subkeys used to encrypt the year, month, and day
+const DATE_V2_ORE_KEY_PREFIX: &[u8] = b"DateV2.ore_key";
+
+impl V2 {
+    /// Encrypt the date
+    pub(crate) fn new(date: (i16, u8, u8), context: &[u8], field: &Field) -> Result<V2, Error> {
+        Ok(V2 {
+            base: V1::encrypt(date, context, DATE_V2_ORE_KEY_PREFIX, field, false)?,
+        })
+    }
+
+    /// Encrypt the date in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        date: (i16, u8, u8),
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V2, Error> {
+        Ok(V2 {
+            base: V1::encrypt(date, context, DATE_V2_ORE_KEY_PREFIX, field, true)?,
+        })
+    }
+
+    /// Re-encrypt a v1 date as v2
+    ///
+    /// The new date only includes left ciphertexts if the old one did, and a date which has been
+    /// made unqueryable stays that way.
+    ///
+    pub(crate) fn from_v1(v1: &V1, context: &[u8], field: &Field) -> Result<V2, Error> {
+        let date = v1.decrypt(context, field)?;
+
+        match v1.ore_parts() {
+            Ok((y, _, _)) if y.has_left() => Self::new_with_unsafe_parts(date, context, field),
+            Ok(_) => Self::new(date, context, field),
+            Err(_) => {
+                let mut v2 = Self::new(date, context, field)?;
+                v2.make_unqueryable();
+                Ok(v2)
+            }
+        }
+    }
+
+    /// Encrypt a date into ORE ciphertexts that can be compared against v2 dates
+    pub(crate) fn ore_query(
+        date: (i16, u8, u8),
+        field: &Field,
+    ) -> Result<(OREv1<2, 256>, OREv1<1, 32>, OREv1<1, 32>), Error> {
+        V1::ore(date, DATE_V2_ORE_KEY_PREFIX, field, true)
+    }
+
+    /// Encrypt a year into an ORE ciphertext that can be compared against v2 dates' years
+    pub(crate) fn ore_query_year(y: i16, field: &Field) -> Result<OREv1<2, 256>, Error> {
+        V1::ore_year(y, DATE_V2_ORE_KEY_PREFIX, field, true)
+    }
+
+    /// Encrypt a month into an ORE ciphertext that can be compared against v2 dates' months
+    pub(crate) fn ore_query_month(m: u8, field: &Field) -> Result<OREv1<1, 32>, Error> {
+        V1::ore_month(m, DATE_V2_ORE_KEY_PREFIX, field, true)
+    }
+
+    /// Encrypt a day into an ORE ciphertext that can be compared against v2 dates' days
+    pub(crate) fn ore_query_day(d: u8, field: &Field) -> Result<OREv1<1, 32>, Error> {
+        V1::ore_day(d, DATE_V2_ORE_KEY_PREFIX, field, true)
+    }
+
+    /// Turn the ciphertext back into a date, or at least a tuple representing a date
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<(i16, u8, u8), Error> {
+        self.base.decrypt(context, field)
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        self.base.key_id()
+    }
+
+    /// Strip out everything that makes a queryable Date queryable
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.base.make_unqueryable();
+    }
+
+    /// Extract the various encrypted y/m/d components, for comparison purposes
+    pub(crate) fn ore_parts(
+        &self,
+    ) -> Result<(&OREv1<2, 256>, &OREv1<1, 32>, &OREv1<1, 32>), Error> {
+        self.base.ore_parts()
+    }
+
+    /// Compare two dates, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        self.base.try_cmp(&other.base)
+    }
+}
+
+impl Ord for V2 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V2 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V2 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V2 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V2::new((1970, 1, 1), b"context", &field()).unwrap();
+
+        assert_eq!((1970, 1, 1), value.decrypt(b"context", &field()).unwrap());
+    }
+
+    #[test]
+    fn dates_with_different_contexts_compare_correctly() {
+        let early = V2::new_with_unsafe_parts((1970, 1, 1), b"row 1", &field()).unwrap();
+        let late = V2::new((2000, 2, 29), b"row 2", &field()).unwrap();
+
+        assert_eq!(Ordering::Less, early.try_cmp(&late).unwrap());
+        assert_eq!(Ordering::Greater, late.try_cmp(&early).unwrap());
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V2::new((1970, 1, 1), b"somecontext", &field()).unwrap();
+        let (y, m, d) = value.ore_parts().unwrap();
+
+        assert!(!y.has_left());
+        assert!(!m.has_left());
+        assert!(!d.has_left());
+    }
+
+    #[test]
+    fn upgrade_preserves_safety() {
+        let safe = V2::from_v1(
+            &V1::new((1970, 1, 1), b"ctx", &field()).unwrap(),
+            b"ctx",
+            &field(),
+        )
+        .unwrap();
+        let not_safe = V2::from_v1(
+            &V1::new_with_unsafe_parts((1970, 1, 1), b"ctx", &field()).unwrap(),
+            b"ctx",
+            &field(),
+        )
+        .unwrap();
+        let mut unqueryable = V1::new((1970, 1, 1), b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+        let unqueryable = V2::from_v1(&unqueryable, b"ctx", &field()).unwrap();
+
+        assert!(!safe.ore_parts().unwrap().0.has_left());
+        assert!(not_safe.ore_parts().unwrap().0.has_left());
+        assert!(matches!(
+            unqueryable.ore_parts(),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert_eq!((1970, 1, 1), unqueryable.decrypt(b"ctx", &field()).unwrap());
+    }
+}
@@ -0,0 +1,110 @@
+//! Encrypted decimal codes, for range-querying decimals
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::{
+    crypto::OREv1,
+    datatype::kith::{Datatype as KithDatatype, Member as KithMember},
+    field::KeyId,
+    Error,
+};
+
+/// Versioning support
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[allow(clippy::large_enum_variant, clippy::missing_docs_in_private_items)] // Unknown is only a placeholder
+enum Ciphertext {
+    #[allow(non_camel_case_types)]
+    v1(OREv1<16, 256>),
+    Unknown,
+}
+
+/// The encrypted value of a decimal, along with its scale, for comparing against stored decimals
+///
+/// This is what you get from [`Decimal::query()`](crate::datatype::Decimal::query), to compare
+/// against stored decimals with
+/// [`Decimal::try_cmp_query()`](crate::datatype::Decimal::try_cmp_query).
+///
+/// The ORE ciphertext is of the decimal's mantissa, which only means anything alongside the
+/// number of decimal places it was scaled to, so the scale is kept (unencrypted) in the code, and
+/// codes with different scales refuse to be compared.
+///
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DecimalCode {
+    /// The ORE ciphertext of the mantissa
+    #[serde(rename = "o")]
+    ore_ciphertext: Ciphertext,
+
+    /// The number of decimal places the mantissa was scaled to
+    #[serde(rename = "s")]
+    scale: u8,
+
+    /// The field key ID which was used to generate this ciphertext
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+impl DecimalCode {
+    /// Create a decimal code from the `OREv1` ciphertext of a decimal's mantissa
+    ///
+    pub(crate) fn from_ore_v1(o: OREv1<16, 256>, scale: u8, key_id: KeyId) -> Self {
+        Self {
+            ore_ciphertext: Ciphertext::v1(o),
+            scale,
+            kid: key_id.into(),
+        }
+    }
+
+    /// The number of decimal places the value was scaled to before it was encrypted
+    ///
+    #[must_use]
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Compare this decimal code with another
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decimal codes were encrypted with different field keys, or at
+    /// different scales, if neither decimal code contains a left ciphertext, or if either decimal
+    /// code is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        if self.scale != other.scale {
+            return Err(Error::ScaleMismatchError(format!(
+                "{} vs {}",
+                self.scale, other.scale
+            )));
+        }
+
+        match (&self.ore_ciphertext, &other.ore_ciphertext) {
+            (Ciphertext::v1(lhs), Ciphertext::v1(rhs)) => lhs.try_cmp(rhs),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
+}
+
+impl KithMember for DecimalCode {}
+
+impl KithDatatype for DecimalCode {
+    fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self.ore_ciphertext {
+            Ciphertext::v1(_) => 1,
+            Ciphertext::Unknown => 0,
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! A decimal number that doesn't lose any digits
+//!
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// The largest number of decimal places a `FixedDecimal` can have
+///
+/// This is as many decimal digits as will always fit in an `i128`.
+///
+pub(crate) const MAX_DIGITS: u8 = 38;
+
+/// An exact decimal number, stored as an integer and a number of decimal places
+///
+/// This is what [`Decimal::new()`](crate::datatype::Decimal::new) and friends actually encrypt.
+/// You don't usually need to create one yourself, because anything which can be converted into a
+/// `FixedDecimal` -- such as an `i64`, a string like `"-1234.56"`, or, with the `rust_decimal`
+/// feature enabled, a `rust_decimal::Decimal` -- can be passed instead.
+///
+/// The value of a `FixedDecimal` is `mantissa * 10^-scale`, so `1234.56` has a mantissa of
+/// `123456` and a scale of `2`.  Because the scale is part of the value, `1.5` and `1.50` are
+/// *not* equal as far as `==` is concerned, even though they're the same number.  Use
+/// [`rescale()`](FixedDecimal::rescale) to get them onto the same footing if that matters.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedDecimal {
+    /// All the digits of the number, with the decimal point taken out
+    mantissa: i128,
+    /// How many of the digits in the mantissa come after the decimal point
+    scale: u8,
+}
+
+impl FixedDecimal {
+    /// Make a new decimal number
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the scale is more than 38.
+    ///
+    pub fn new(mantissa: i128, scale: u8) -> Result<Self, Error> {
+        if scale > MAX_DIGITS {
+            return Err(Error::RangeError(format!(
+                "scale {scale} is too large (must be no more than {MAX_DIGITS})"
+            )));
+        }
+
+        Ok(Self { mantissa, scale })
+    }
+
+    /// All the digits of the number, with the decimal point taken out
+    ///
+    #[must_use]
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// How many digits come after the decimal point
+    ///
+    #[must_use]
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Express the same number with a different number of decimal places
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the new scale is too large, or if the
+    /// number can't be represented exactly with the new scale -- either because it has non-zero
+    /// digits that would have to be thrown away, or because it would no longer fit.
+    ///
+    pub fn rescale(&self, scale: u8) -> Result<Self, Error> {
+        let mantissa = if scale >= self.scale {
+            self.mantissa
+                .checked_mul(Self::power_of_ten(scale.saturating_sub(self.scale))?)
+                .ok_or_else(|| {
+                    Error::RangeError(format!(
+                        "{self} is too large to have {scale} decimal places"
+                    ))
+                })?
+        } else {
+            let divisor = Self::power_of_ten(self.scale.saturating_sub(scale))?;
+
+            if self.mantissa.checked_rem(divisor) != Some(0) {
+                return Err(Error::RangeError(format!(
+                    "{self} cannot be represented exactly with {scale} decimal places"
+                )));
+            }
+
+            self.mantissa
+                .checked_div(divisor)
+                .ok_or_else(|| Error::RangeError(format!("{self} cannot be rescaled")))?
+        };
+
+        Self::new(mantissa, scale)
+    }
+
+    /// Whether the number has no more than the given number of significant digits
+    ///
+    pub(crate) fn fits_in(&self, digits: u8) -> bool {
+        10u128
+            .checked_pow(u32::from(digits))
+            .is_none_or(|limit| self.mantissa.unsigned_abs() < limit)
+    }
+
+    /// Ten to the power of whatever
+    ///
+    fn power_of_ten(exp: u8) -> Result<i128, Error> {
+        10i128
+            .checked_pow(u32::from(exp))
+            .ok_or_else(|| Error::RangeError(format!("10^{exp} is too large")))
+    }
+}
+
+impl fmt::Display for FixedDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = usize::from(self.scale);
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa.unsigned_abs(),
+            width = scale.saturating_add(1)
+        );
+        let (int, frac) = digits.split_at(digits.len().saturating_sub(scale));
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+
+        if frac.is_empty() {
+            write!(f, "{sign}{int}")
+        } else {
+            write!(f, "{sign}{int}.{frac}")
+        }
+    }
+}
+
+impl FromStr for FixedDecimal {
+    type Err = Error;
+
+    /// Parse a plain decimal number, like `-1234.56`
+    ///
+    /// The scale of the result is the number of digits after the decimal point, so `"1.50"` has a
+    /// scale of `2`.  Exponents, thousands separators, and the like are not supported.
+    ///
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::RangeError(format!("{s:?} is not a decimal number"));
+
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+        if (int.is_empty() && frac.is_empty())
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let scale = u8::try_from(frac.len())
+            .map_err(|e| Error::RangeError(format!("{s:?} has too many decimal places ({e})")))?;
+        let magnitude = [int, frac]
+            .concat()
+            .parse::<i128>()
+            .map_err(|e| Error::RangeError(format!("{s:?} has too many digits ({e})")))?;
+
+        Self::new(
+            if negative {
+                magnitude.checked_neg().ok_or_else(invalid)?
+            } else {
+                magnitude
+            },
+            scale,
+        )
+    }
+}
+
+impl TryFrom<&str> for FixedDecimal {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Error> {
+        s.parse()
+    }
+}
+
+impl From<i64> for FixedDecimal {
+    fn from(i: i64) -> Self {
+        Self {
+            mantissa: i128::from(i),
+            scale: 0,
+        }
+    }
+}
+
+impl From<i128> for FixedDecimal {
+    fn from(i: i128) -> Self {
+        Self {
+            mantissa: i,
+            scale: 0,
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl TryFrom<rust_decimal::Decimal> for FixedDecimal {
+    type Error = Error;
+
+    fn try_from(d: rust_decimal::Decimal) -> Result<Self, Error> {
+        Self::new(
+            d.mantissa(),
+            u8::try_from(d.scale())
+                .map_err(|e| Error::RangeError(format!("{d} has too large a scale ({e})")))?,
+        )
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl TryFrom<FixedDecimal> for rust_decimal::Decimal {
+    type Error = Error;
+
+    fn try_from(d: FixedDecimal) -> Result<Self, Error> {
+        rust_decimal::Decimal::try_from_i128_with_scale(d.mantissa, u32::from(d.scale)).map_err(
+            |e| Error::RangeError(format!("{d} cannot be represented by rust_decimal ({e})")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fd(s: &str) -> FixedDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        for s in [
+            "0",
+            "-1",
+            "1234.56",
+            "-0.05",
+            "0.000",
+            "99999999999999999999999999999999999999",
+        ] {
+            assert_eq!(s, fd(s).to_string());
+        }
+
+        assert_eq!("12.5", fd("+12.5").to_string());
+        assert_eq!("0.5", fd(".5").to_string());
+    }
+
+    #[test]
+    fn parsing_is_exact() {
+        let d = fd("-1234.560");
+
+        assert_eq!(-1_234_560, d.mantissa());
+        assert_eq!(3, d.scale());
+    }
+
+    #[test]
+    fn nonsense_is_rejected() {
+        for s in ["", "-", ".", "1.2.3", "1e5", "12,345", " 1", "0x10", "--1"] {
+            assert!(
+                matches!(s.parse::<FixedDecimal>(), Err(Error::RangeError(_))),
+                "{s:?}"
+            );
+        }
+
+        assert!(matches!(
+            FixedDecimal::new(1, 39),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn rescaling_is_exact() {
+        assert_eq!(fd("1.500"), fd("1.5").rescale(3).unwrap());
+        assert_eq!(fd("-2"), fd("-2.000").rescale(0).unwrap());
+        assert!(matches!(fd("1.25").rescale(1), Err(Error::RangeError(_))));
+        assert!(matches!(
+            FixedDecimal::from(i128::MAX).rescale(1),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn significant_digits_are_counted() {
+        assert!(fd("999.99").fits_in(5));
+        assert!(!fd("-1000.00").fits_in(5));
+        assert!(fd("0.00").fits_in(1));
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn rust_decimals_convert_both_ways() {
+        for s in [
+            "0",
+            "-1234.56",
+            "0.0000000001",
+            "79228162514264337593543950335",
+        ] {
+            let rd = rust_decimal::Decimal::from_str(s).unwrap();
+            let d = FixedDecimal::try_from(rd).unwrap();
+
+            assert_eq!(fd(s), d);
+            assert_eq!(rd, rust_decimal::Decimal::try_from(d).unwrap());
+        }
+
+        assert!(matches!(
+            rust_decimal::Decimal::try_from(fd("79228162514264337593543950336")),
+            Err(Error::RangeError(_))
+        ));
+    }
+}
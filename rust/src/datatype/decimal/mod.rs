@@ -0,0 +1,411 @@
+//! Store and query exact decimal numbers in an encrypted form
+//!
+
+mod decimal_code;
+mod fixed_decimal;
+mod spec;
+mod v1;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub use self::{decimal_code::DecimalCode, fixed_decimal::FixedDecimal, spec::DecimalSpec};
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted, queryable decimal number
+///
+/// Decimals are for numbers which need to be stored exactly, with a fixed number of decimal
+/// places, like amounts of money.  Every value in a field is stored with the precision and scale
+/// declared by the field's [`DecimalSpec`], so `12.5` in a field declared as
+/// `DecimalSpec::new(9, 2)` is stored (and decrypted) as `12.50`.
+///
+/// Decimals encrypted with different scales can't be compared, so make sure you always use the
+/// same spec for a given field.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum Decimal {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl Decimal {
+    /// Create a new encrypted, queryable decimal
+    ///
+    /// The value can be anything that converts into a [`FixedDecimal`], such as an `i64`, a
+    /// string like `"1234.56"`, or (with the `rust_decimal` feature enabled) a
+    /// `rust_decimal::Decimal`.  It is converted to the scale given in the `spec` before being
+    /// encrypted.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `FixedDecimal`, has more decimal places than the spec's scale, or has more significant
+    /// digits than the spec's precision, and can return an error if the process of encrypting the
+    /// data fails.
+    ///
+    pub fn new<D>(d: D, spec: DecimalSpec, context: &[u8], field: &Field) -> Result<Decimal, Error>
+    where
+        D: TryInto<FixedDecimal>,
+        Error: From<D::Error>,
+    {
+        Ok(Decimal::v1(Box::new(V1::new(
+            spec.conform(d.try_into()?)?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Create a new encrypted, queryable decimal with degraded security
+    ///
+    /// The value can be anything that converts into a [`FixedDecimal`], as per
+    /// [`Decimal::new()`](Decimal::new).
+    ///
+    /// While the decimal itself is securely encrypted, the ciphertexts produced by this function
+    /// may contain components that allow an attacker to infer, either precisely or approximately,
+    /// the plaintext value.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`Decimal::new()`](Decimal::new).
+    ///
+    pub fn new_with_unsafe_parts<D>(
+        d: D,
+        spec: DecimalSpec,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<Decimal, Error>
+    where
+        D: TryInto<FixedDecimal>,
+        Error: From<D::Error>,
+    {
+        Ok(Decimal::v1(Box::new(V1::new_with_unsafe_parts(
+            spec.conform(d.try_into()?)?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Decrypt the decimal
+    ///
+    /// The result always has the scale that the decimal was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the decimal could not be decrypted for some reason, such as if the
+    /// wrong field was provided, or the decryption context was incorrect.  See
+    /// [`Decimal::new()`](Decimal::new) for more details about encryption and decryption
+    /// contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<FixedDecimal, Error> {
+        match self {
+            Decimal::v1(d) => d.decrypt(context, field),
+            Decimal::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the decimal, and convert it into whatever type you like
+    ///
+    /// Anything that a [`FixedDecimal`] can be converted into will do, such as (with the
+    /// `rust_decimal` feature enabled) a `rust_decimal::Decimal`.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error in all the same situations as
+    /// [`Decimal::decrypt()`](Decimal::decrypt), as well as if the decrypted decimal cannot be
+    /// represented by the requested type.
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: TryFrom<FixedDecimal>,
+        Error: From<T::Error>,
+    {
+        Ok(T::try_from(self.decrypt(context, field)?)?)
+    }
+
+    /// Compare this decimal with another, without panicking if it can't be done
+    ///
+    /// Comparing decimals via `Ord` will panic if (say) one of them has been made unqueryable,
+    /// or they came from different fields.  If you'd rather find out about that via an error,
+    /// this is the method for you.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decimals were encrypted with different field keys or different
+    /// scales, if either has been made unqueryable, if neither has the left ciphertext required
+    /// to perform the comparison, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Decimal::v1(s), Decimal::v1(o)) => s.try_cmp(o),
+            (Decimal::Unknown, _) | (_, Decimal::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this decimal is equal to another, without panicking if it can't be
+    /// done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`Decimal::try_cmp()`](Decimal::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// The number of decimal places the decimal was encrypted with
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decimal is of an unknown version.
+    ///
+    pub fn scale(&self) -> Result<u8, Error> {
+        match self {
+            Decimal::v1(d) => Ok(d.scale()),
+            Decimal::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored decimals
+    ///
+    /// This is what you need for range queries, like `WHERE balance < 0`.  The value can be
+    /// anything that converts into a [`FixedDecimal`], and is converted to the scale given in
+    /// the `spec` before being encrypted with the field's ORE key, so the result is suitable for
+    /// passing to [`Decimal::try_cmp_query()`](Decimal::try_cmp_query) for any decimal encrypted
+    /// using the same field and spec, regardless of the context it was encrypted with.
+    ///
+    /// The query records the scale it was made with, so comparing it against a decimal that was
+    /// encrypted with a different scale is an error, just like comparing two such decimals.
+    ///
+    /// The value is never rounded, because which way to round depends on the comparison you're
+    /// making, so a bound with more decimal places than the spec's scale is refused.  Round it
+    /// yourself, tweaking the comparison to suit: on a field with a scale of 2,
+    /// `salary > 100.005` is the same as `salary >= 100.01`, and `salary < 100.005` is the same
+    /// as `salary <= 100.00`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `FixedDecimal` that conforms to the spec, and can return an error if the encryption
+    /// process fails.
+    ///
+    pub fn query<D>(d: D, spec: DecimalSpec, field: &Field) -> Result<Kith<DecimalCode>, Error>
+    where
+        D: TryInto<FixedDecimal>,
+        Error: From<D::Error>,
+    {
+        let mut k = Kith::new();
+        k.add_member(DecimalCode::from_ore_v1(
+            V1::ore(spec.conform(d.try_into()?)?, field, true)?,
+            spec.scale(),
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare this decimal with the decimal represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by [`Decimal::query()`](Decimal::query),
+    /// using the same field and spec as this decimal.  The result says where this decimal sorts
+    /// relative to the queried one, so `Ordering::Greater` means that this decimal is larger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decimal has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this decimal, if the query was made with a different
+    /// scale to the one this decimal was encrypted with, or if the decimal is of an unknown
+    /// version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<DecimalCode>) -> Result<Ordering, Error> {
+        let code = match self {
+            Decimal::v1(d) => d
+                .ore_ciphertext()
+                .map(|o| DecimalCode::from_ore_v1(o.clone(), d.scale(), d.key_id())),
+            Decimal::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(code, DecimalCode::try_cmp)
+    }
+
+    /// Remove the ability to perform any queries on this value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted decimal, without any
+    /// ability to query it.  In that case, you can save a fair chunk of space by calling this
+    /// method before you serialise it.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the object could not be made unqueryable, or if an attempt was made
+    /// to make an Unknown version unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            Decimal::v1(d) => {
+                d.make_unqueryable();
+                Ok(())
+            }
+            Decimal::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl Ord for Decimal {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl KithDatatype for Decimal {
+    fn key_id(&self) -> KeyId {
+        match self {
+            Decimal::v1(d) => d.key_id(),
+            Decimal::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            Decimal::v1(_) => 1,
+            Decimal::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn money() -> DecimalSpec {
+        DecimalSpec::new(9, 2).unwrap()
+    }
+
+    #[test]
+    fn value_round_trips_at_declared_scale() {
+        let value = Decimal::new("1234.5", money(), b"row 1", &field()).unwrap();
+        let d = value.decrypt(b"row 1", &field()).unwrap();
+
+        assert_eq!("1234.50", d.to_string());
+        assert_eq!(2, value.scale().unwrap());
+    }
+
+    #[test]
+    fn values_outside_the_spec_are_rejected() {
+        for v in ["0.001", "10000000", "-10000000.00", "lots"] {
+            assert!(
+                matches!(
+                    Decimal::new(v, money(), b"row 1", &field()),
+                    Err(Error::RangeError(_))
+                ),
+                "{v}"
+            );
+        }
+    }
+
+    #[test]
+    fn queries_are_conformed_to_the_declared_scale() {
+        let balance = Decimal::new("-12.34", money(), b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Equal,
+            balance
+                .try_cmp_query(&Decimal::query("-12.340", money(), &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Greater,
+            balance
+                .try_cmp_query(&Decimal::query("-12.35", money(), &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn queries_finer_than_the_declared_scale_are_refused() {
+        assert!(matches!(
+            Decimal::query("100.005", money(), &field()),
+            Err(Error::RangeError(_))
+        ));
+
+        let salary = Decimal::new("100.01", money(), b"row 1", &field()).unwrap();
+        assert_eq!(
+            Ordering::Equal,
+            salary
+                .try_cmp_query(&Decimal::query("100.01", money(), &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn negative_decimals_sort_first() {
+        let negative =
+            Decimal::new_with_unsafe_parts("-0.01", money(), b"row 1", &field()).unwrap();
+        let zero = Decimal::new(0i64, money(), b"row 2", &field()).unwrap();
+        let positive = Decimal::new("0.01", money(), b"row 3", &field()).unwrap();
+
+        assert!(negative < zero);
+        assert!(negative < positive);
+    }
+
+    #[test]
+    fn different_scales_cannot_be_compared() {
+        let cents = Decimal::new_with_unsafe_parts(1i64, money(), b"row 1", &field()).unwrap();
+        let dollars =
+            Decimal::new(1i64, DecimalSpec::new(9, 0).unwrap(), b"row 2", &field()).unwrap();
+
+        assert!(matches!(
+            cents.try_cmp(&dollars),
+            Err(Error::ScaleMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn queries_with_a_different_scale_are_refused() {
+        let cents = Decimal::new(1i64, money(), b"row 1", &field()).unwrap();
+        let query = Decimal::query(1i64, DecimalSpec::new(9, 0).unwrap(), &field()).unwrap();
+
+        assert!(matches!(
+            cents.try_cmp_query(&query),
+            Err(Error::ScaleMismatchError(_))
+        ));
+    }
+}
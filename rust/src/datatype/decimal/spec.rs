@@ -0,0 +1,126 @@
+//! The shape of the numbers that go into a decimal field
+//!
+
+use super::{fixed_decimal::MAX_DIGITS, FixedDecimal};
+use crate::Error;
+
+/// The precision and scale of the decimal numbers stored in a field
+///
+/// This is the equivalent of SQL's `NUMERIC(precision, scale)`: the `precision` is the total
+/// number of significant digits a value can have, and the `scale` is how many of those digits
+/// come after the decimal point.  So a field for amounts of money up to (but not including) ten
+/// million dollars, to the cent, would be `DecimalSpec::new(9, 2)`.
+///
+/// Every value is converted to the declared scale before it is encrypted, so that they can all
+/// be compared with one another.  Values with more decimal places than the scale allows are
+/// rejected, rather than rounded, so nothing is ever silently lost.
+///
+/// ```rust
+/// use enquo_core::datatype::DecimalSpec;
+///
+/// let money = DecimalSpec::new(9, 2).unwrap();
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecimalSpec {
+    /// The maximum number of significant digits
+    precision: u8,
+    /// The number of digits after the decimal point
+    scale: u8,
+}
+
+impl DecimalSpec {
+    /// Declare the precision and scale of a decimal field
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the precision is not between 1 and 38, or
+    /// if the scale is larger than the precision.
+    ///
+    pub fn new(precision: u8, scale: u8) -> Result<Self, Error> {
+        if precision == 0 || precision > MAX_DIGITS {
+            return Err(Error::RangeError(format!(
+                "precision {precision} is not valid (must be 1-{MAX_DIGITS})"
+            )));
+        }
+
+        if scale > precision {
+            return Err(Error::RangeError(format!(
+                "scale {scale} cannot be larger than precision {precision}"
+            )));
+        }
+
+        Ok(Self { precision, scale })
+    }
+
+    /// The maximum number of significant digits
+    ///
+    #[must_use]
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// The number of digits after the decimal point
+    ///
+    #[must_use]
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Convert a value to this spec's scale, making sure it still fits within the precision
+    ///
+    pub(crate) fn conform(self, d: FixedDecimal) -> Result<FixedDecimal, Error> {
+        let d = d.rescale(self.scale)?;
+
+        if d.fits_in(self.precision) {
+            Ok(d)
+        } else {
+            Err(Error::RangeError(format!(
+                "{d} has more than {} significant digits",
+                self.precision
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fd(s: &str) -> FixedDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn silly_specs_are_rejected() {
+        assert!(DecimalSpec::new(38, 38).is_ok());
+
+        for (p, s) in [(0, 0), (39, 2), (5, 6)] {
+            assert!(
+                matches!(DecimalSpec::new(p, s), Err(Error::RangeError(_))),
+                "({p}, {s})"
+            );
+        }
+    }
+
+    #[test]
+    fn values_are_conformed() {
+        let spec = DecimalSpec::new(5, 2).unwrap();
+
+        assert_eq!(fd("12.50"), spec.conform(fd("12.5")).unwrap());
+        assert_eq!(fd("-999.99"), spec.conform(fd("-999.99")).unwrap());
+        assert_eq!(fd("7.00"), spec.conform(FixedDecimal::from(7i64)).unwrap());
+    }
+
+    #[test]
+    fn nonconforming_values_are_rejected() {
+        let spec = DecimalSpec::new(5, 2).unwrap();
+
+        for v in ["1000", "-1000.00", "0.125"] {
+            assert!(
+                matches!(spec.conform(fd(v)), Err(Error::RangeError(_))),
+                "{v}"
+            );
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! The first version of the Decimal datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use super::FixedDecimal;
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    util::i128_to_orderable_u128,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The decimal in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the decimal's mantissa
+    #[serde(rename = "o")]
+    ore_ciphertext: Option<OREv1<16, 256>>,
+    /// The scale that the decimal was converted to before encryption, which is the same for every
+    /// value in the field, and so doesn't give anything away
+    #[serde(rename = "s")]
+    scale: u8,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// What's actually encrypted into the AES ciphertext
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredDecimal {
+    /// The mantissa
+    m: i128,
+    /// The scale
+    s: u8,
+}
+
+/// Identifier for the subkey used to encrypt the ORE ciphertext
+const DECIMAL_V1_ORE_KEY_IDENTIFIER: &[u8] = b"DecimalV1.ore_key";
+
+impl V1 {
+    /// Encrypt the decimal, which must already have been conformed to the field's spec
+    pub(crate) fn new(d: FixedDecimal, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(d, context, field, false)
+    }
+
+    /// Encrypt the decimal in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        d: FixedDecimal,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(d, context, field, true)
+    }
+
+    /// Encrypt the decimal for decryption, and its scaled mantissa for querying
+    ///
+    fn encrypt(
+        d: FixedDecimal,
+        context: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
+        let s_d = StoredDecimal {
+            m: d.mantissa(),
+            s: d.scale(),
+        };
+
+        let v = cbor!(s_d).map_err(|e| {
+            Error::EncodingError(format!("failed to convert decimal to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode decimal value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(Self::ore(d, field, include_left)?),
+            scale: d.scale(),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt a decimal into an order-revealing ciphertext
+    ///
+    /// Only the mantissa is encrypted, so this only sorts correctly against other decimals with
+    /// the same scale.
+    ///
+    pub(crate) fn ore(
+        d: FixedDecimal,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<16, 256>, Error> {
+        let u = i128_to_orderable_u128(d.mantissa());
+
+        if include_left {
+            Ok(OREv1::<16, 256>::new_with_left(
+                u,
+                DECIMAL_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        } else {
+            Ok(OREv1::<16, 256>::new(
+                u,
+                DECIMAL_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        }
+    }
+
+    /// Turn the ciphertext back into a decimal
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<FixedDecimal, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let s_d = ciborium::de::from_reader::<StoredDecimal, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        FixedDecimal::new(s_d.m, s_d.s)
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// The scale of the encrypted decimal
+    pub(crate) fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Return the ORE ciphertext, if the decimal hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<16, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.ore_ciphertext = None;
+    }
+
+    /// Compare two decimals, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        if self.scale != other.scale {
+            return Err(Error::ScaleMismatchError(format!(
+                "{} vs {}",
+                self.scale, other.scale
+            )));
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
+}
+
+impl Ord for V1 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V1 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn fd(s: &str) -> FixedDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V1::new(fd("-1234.50"), b"context", &field()).unwrap();
+
+        assert_eq!(fd("-1234.50"), value.decrypt(b"context", &field()).unwrap());
+    }
+
+    #[test]
+    fn huge_values_round_trip() {
+        for s in [
+            "-99999999999999999999999999999999999999",
+            "0.99999999999999999999999999999999999999",
+        ] {
+            let value = V1::new(fd(s), b"context", &field()).unwrap();
+
+            assert_eq!(fd(s), value.decrypt(b"context", &field()).unwrap());
+        }
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(fd("1.00"), b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(fd("1.00"), b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn decimals_compare_correctly() {
+        let values = [
+            fd("-99999999999999999999999999999999999.999"),
+            fd("-1.000"),
+            fd("-0.001"),
+            fd("0.000"),
+            fd("0.001"),
+            fd("1.000"),
+            fd("99999999999999999999999999999999999.999"),
+        ];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l} vs {r}");
+            }
+        }
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let value = V1::new_with_unsafe_parts(fd("1.00"), b"ctx", &field()).unwrap();
+        let safe = V1::new(fd("1.00"), b"ctx", &field()).unwrap();
+        let rescaled = V1::new_with_unsafe_parts(fd("1.0"), b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::new(fd("1.00"), b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&rescaled),
+            Err(Error::ScaleMismatchError(_))
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
@@ -0,0 +1,320 @@
+//! Store and query lengths of time in an encrypted form
+//!
+
+mod signed_duration;
+mod v1;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub use self::signed_duration::SignedDuration;
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted, queryable duration
+///
+/// A duration is a length of time, to the nanosecond, such as the length of a hospital stay or
+/// the time taken to resolve a support ticket.  Durations can be negative, for those times when
+/// something finished before it started.
+///
+/// Durations are an exact number of seconds; calendar-based intervals like "one month" don't
+/// have a fixed length, and so can't be stored (or sorted) as a `Duration`.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum Duration {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl Duration {
+    /// Create a new encrypted, queryable duration
+    ///
+    /// The duration can be anything that converts into a [`SignedDuration`], such as an `i64`
+    /// number of seconds, a `std::time::Duration`, or (with the appropriate features enabled) a
+    /// `chrono::TimeDelta` or `time::Duration`.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `SignedDuration`, and can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new<D>(d: D, context: &[u8], field: &Field) -> Result<Duration, Error>
+    where
+        D: TryInto<SignedDuration>,
+        Error: From<D::Error>,
+    {
+        Ok(Duration::v1(Box::new(V1::new(
+            d.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Create a new encrypted, queryable duration with degraded security
+    ///
+    /// The duration can be anything that converts into a [`SignedDuration`], as per
+    /// [`Duration::new()`](Duration::new).
+    ///
+    /// While the duration itself is securely encrypted, the ciphertexts produced by this function
+    /// may contain components that allow an attacker to infer, either precisely or approximately,
+    /// the plaintext value.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `SignedDuration`, and can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new_with_unsafe_parts<D>(d: D, context: &[u8], field: &Field) -> Result<Duration, Error>
+    where
+        D: TryInto<SignedDuration>,
+        Error: From<D::Error>,
+    {
+        Ok(Duration::v1(Box::new(V1::new_with_unsafe_parts(
+            d.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Decrypt the duration
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the duration could not be decrypted for some reason, such as if the
+    /// wrong field was provided, or the decryption context was incorrect.  See
+    /// [`Duration::new()`](Duration::new) for more details about encryption and decryption
+    /// contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<SignedDuration, Error> {
+        match self {
+            Duration::v1(d) => d.decrypt(context, field),
+            Duration::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the duration, and convert it into whatever type you like
+    ///
+    /// Anything that a [`SignedDuration`] can be converted into will do, such as a
+    /// `std::time::Duration` (as long as the duration isn't negative), or (with the appropriate
+    /// features enabled) a `chrono::TimeDelta` or `time::Duration`.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error in all the same situations as
+    /// [`Duration::decrypt()`](Duration::decrypt), as well as if the decrypted duration cannot be
+    /// represented by the requested type.
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: TryFrom<SignedDuration>,
+        Error: From<T::Error>,
+    {
+        Ok(T::try_from(self.decrypt(context, field)?)?)
+    }
+
+    /// Compare this duration with another, without panicking if it can't be done
+    ///
+    /// Comparing durations via `Ord` will panic if (say) one of them has been made unqueryable,
+    /// or they came from different fields.  If you'd rather find out about that via an error,
+    /// this is the method for you.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the durations were encrypted with different field keys, if either has
+    /// been made unqueryable, if neither has the left ciphertext required to perform the
+    /// comparison, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Duration::v1(s), Duration::v1(o)) => s.try_cmp(o),
+            (Duration::Unknown, _) | (_, Duration::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this duration is equal to another, without panicking if it can't be
+    /// done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as
+    /// [`Duration::try_cmp()`](Duration::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored durations
+    ///
+    /// This is what you need for range queries, like `WHERE length_of_stay > '7 days'`.  The
+    /// duration can be anything that converts into a [`SignedDuration`], and is encrypted with the
+    /// field's ORE key, so the result is suitable for passing to
+    /// [`Duration::try_cmp_query()`](Duration::try_cmp_query) for any duration encrypted using the
+    /// same field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `SignedDuration`, and can return an error if the encryption process fails.
+    ///
+    pub fn query<D>(d: D, field: &Field) -> Result<Kith<ORE<12, 256>>, Error>
+    where
+        D: TryInto<SignedDuration>,
+        Error: From<D::Error>,
+    {
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V1::ore(d.try_into()?, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare this duration with the duration represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by
+    /// [`Duration::query()`](Duration::query), using the same field as this duration.  The result
+    /// says where this duration sorts relative to the queried one, so `Ordering::Greater` means
+    /// that this duration is longer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duration has been made unqueryable, if the query does not contain
+    /// a value which can be compared against this duration, or if the duration is of an unknown
+    /// version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<ORE<12, 256>>) -> Result<Ordering, Error> {
+        let ore = match self {
+            Duration::v1(d) => d
+                .ore_ciphertext()
+                .map(|o| ORE::from_ore_v1(o.clone(), d.key_id())),
+            Duration::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(ore, ORE::try_cmp)
+    }
+
+    /// Remove the ability to perform any queries on this value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted duration, without any
+    /// ability to query it.  In that case, you can save a fair chunk of space by calling this
+    /// method before you serialise it.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the object could not be made unqueryable, or if an attempt was made
+    /// to make an Unknown version unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            Duration::v1(d) => {
+                d.make_unqueryable();
+                Ok(())
+            }
+            Duration::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl Ord for Duration {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Duration {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Duration {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Duration {}
+
+impl KithDatatype for Duration {
+    fn key_id(&self) -> KeyId {
+        match self {
+            Duration::v1(d) => d.key_id(),
+            Duration::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            Duration::v1(_) => 1,
+            Duration::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn negative_durations_sort_first() {
+        let negative = Duration::new_with_unsafe_parts(-DAY, b"row 1", &field()).unwrap();
+        let zero = Duration::new(0, b"row 2", &field()).unwrap();
+
+        assert!(negative < zero);
+        assert_eq!(
+            Ordering::Less,
+            negative
+                .try_cmp_query(&Duration::query((-1, 999_999_999), &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn fractional_negative_durations_sort_below_whole_ones() {
+        let value = Duration::new((-2, 500_000_000), b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Less,
+            value
+                .try_cmp_query(&Duration::query(-1, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Greater,
+            value
+                .try_cmp_query(&Duration::query(-2, &field()).unwrap())
+                .unwrap()
+        );
+        let d = value.decrypt(b"row 1", &field()).unwrap();
+        assert_eq!((-2, 500_000_000), (d.secs(), d.nanos()));
+    }
+}
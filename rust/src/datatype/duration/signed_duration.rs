@@ -0,0 +1,256 @@
+//! A length of time that can go either way
+//!
+
+use crate::Error;
+
+/// The number of nanoseconds in a second
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// A span of time, positive or negative, with nanosecond precision
+///
+/// This is what [`Duration::new()`](crate::datatype::Duration::new) and friends actually
+/// encrypt.  You don't usually need to create one yourself, because anything which can be
+/// converted into a `SignedDuration` -- such as an `i64` number of seconds, a
+/// `std::time::Duration`, or, with the `chrono` or `time` features enabled, a `chrono::TimeDelta`
+/// or `time::Duration` -- can be passed instead.
+///
+/// Internally, the duration is stored as a whole number of seconds, which may be negative, plus a
+/// number of nanoseconds which is never negative.  So -1.5 seconds is `-2` seconds plus
+/// `500_000_000` nanoseconds.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SignedDuration {
+    /// Whole seconds, rounded towards negative infinity
+    secs: i64,
+    /// Nanoseconds to add to `secs`
+    nanos: u32,
+}
+
+impl SignedDuration {
+    /// Make a new duration
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if `nanos` is a second or more.
+    ///
+    pub fn new(secs: i64, nanos: u32) -> Result<Self, Error> {
+        if nanos >= NANOS_PER_SECOND {
+            return Err(Error::RangeError(format!(
+                "{nanos} nanoseconds is not less than one second"
+            )));
+        }
+
+        Ok(Self { secs, nanos })
+    }
+
+    /// Make a new duration from seconds and nanoseconds which have the same sign, which is how
+    /// `chrono` and `time` present them
+    ///
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn from_signed_parts(secs: i64, nanos: i32) -> Result<Self, Error> {
+        let out_of_range = || Error::RangeError(format!("{secs}s + {nanos}ns is out of range"));
+
+        if nanos < 0 {
+            Self::new(
+                secs.checked_sub(1).ok_or_else(out_of_range)?,
+                nanos
+                    .checked_add(1_000_000_000)
+                    .and_then(|n| u32::try_from(n).ok())
+                    .ok_or_else(out_of_range)?,
+            )
+        } else {
+            Self::new(secs, nanos.unsigned_abs())
+        }
+    }
+
+    /// Split the duration into seconds and nanoseconds which have the same sign, which is how
+    /// `time` likes them
+    ///
+    #[cfg(feature = "time")]
+    fn signed_parts(&self) -> Result<(i64, i32), Error> {
+        let nanos = i32::try_from(self.nanos)
+            .map_err(|e| Error::RangeError(format!("{self:?} is out of range ({e})")))?;
+
+        if self.secs < 0 && nanos > 0 {
+            Ok((
+                self.secs.saturating_add(1),
+                nanos.saturating_sub(1_000_000_000),
+            ))
+        } else {
+            Ok((self.secs, nanos))
+        }
+    }
+
+    /// Whole seconds, rounded towards negative infinity
+    ///
+    #[must_use]
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Nanoseconds to add to [`secs()`](SignedDuration::secs), always between 0 and 999,999,999
+    ///
+    #[must_use]
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    /// Whether this duration is less than zero
+    ///
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.secs < 0
+    }
+}
+
+impl From<i64> for SignedDuration {
+    fn from(secs: i64) -> Self {
+        Self { secs, nanos: 0 }
+    }
+}
+
+impl TryFrom<(i64, u32)> for SignedDuration {
+    type Error = Error;
+
+    fn try_from(d: (i64, u32)) -> Result<Self, Error> {
+        let (secs, nanos) = d;
+        Self::new(secs, nanos)
+    }
+}
+
+impl TryFrom<std::time::Duration> for SignedDuration {
+    type Error = Error;
+
+    fn try_from(d: std::time::Duration) -> Result<Self, Error> {
+        Self::new(
+            i64::try_from(d.as_secs())
+                .map_err(|e| Error::RangeError(format!("{d:?} is too long ({e})")))?,
+            d.subsec_nanos(),
+        )
+    }
+}
+
+impl TryFrom<SignedDuration> for std::time::Duration {
+    type Error = Error;
+
+    fn try_from(d: SignedDuration) -> Result<Self, Error> {
+        let secs = u64::try_from(d.secs).map_err(|e| {
+            Error::RangeError(format!(
+                "{d:?} cannot be represented by std::time::Duration ({e})"
+            ))
+        })?;
+
+        Ok(std::time::Duration::new(secs, d.nanos))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::TimeDelta> for SignedDuration {
+    type Error = Error;
+
+    fn try_from(d: chrono::TimeDelta) -> Result<Self, Error> {
+        Self::from_signed_parts(d.num_seconds(), d.subsec_nanos())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<SignedDuration> for chrono::TimeDelta {
+    type Error = Error;
+
+    fn try_from(d: SignedDuration) -> Result<Self, Error> {
+        chrono::TimeDelta::new(d.secs, d.nanos)
+            .ok_or_else(|| Error::RangeError(format!("{d:?} cannot be represented by chrono")))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::Duration> for SignedDuration {
+    type Error = Error;
+
+    fn try_from(d: time::Duration) -> Result<Self, Error> {
+        Self::from_signed_parts(d.whole_seconds(), d.subsec_nanoseconds())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<SignedDuration> for time::Duration {
+    type Error = Error;
+
+    fn try_from(d: SignedDuration) -> Result<Self, Error> {
+        let (secs, nanos) = d.signed_parts()?;
+
+        Ok(time::Duration::new(secs, nanos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nanos_must_be_less_than_a_second() {
+        assert!(SignedDuration::new(-1, 999_999_999).is_ok());
+        assert!(matches!(
+            SignedDuration::new(0, 1_000_000_000),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    #[test]
+    fn signed_parts_are_normalised() {
+        let d = SignedDuration::from_signed_parts(-1, -500_000_000).unwrap();
+
+        assert_eq!(-2, d.secs());
+        assert_eq!(500_000_000, d.nanos());
+        assert!(d.is_negative());
+        assert!(d < SignedDuration::from(-1));
+        assert!(d > SignedDuration::from(-2));
+    }
+
+    #[test]
+    fn std_durations_convert_both_ways() {
+        let std = std::time::Duration::new(86_400, 1);
+        let d = SignedDuration::try_from(std).unwrap();
+
+        assert_eq!((86_400, 1), (d.secs(), d.nanos()));
+        assert_eq!(std, std::time::Duration::try_from(d).unwrap());
+        assert!(matches!(
+            std::time::Duration::try_from(SignedDuration::from(-1)),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_durations_convert_both_ways() {
+        for delta in [
+            chrono::TimeDelta::milliseconds(-1500),
+            chrono::TimeDelta::nanoseconds(1),
+            chrono::TimeDelta::days(-3),
+        ] {
+            let d = SignedDuration::try_from(delta).unwrap();
+
+            assert_eq!(delta, chrono::TimeDelta::try_from(d).unwrap());
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_durations_convert_both_ways() {
+        for delta in [
+            time::Duration::milliseconds(-1500),
+            time::Duration::nanoseconds(1),
+            time::Duration::days(-3),
+        ] {
+            let d = SignedDuration::try_from(delta).unwrap();
+
+            assert_eq!(delta, time::Duration::try_from(d).unwrap());
+        }
+
+        assert_eq!(
+            SignedDuration::new(-2, 500_000_000).unwrap(),
+            SignedDuration::try_from(time::Duration::milliseconds(-1500)).unwrap()
+        );
+    }
+}
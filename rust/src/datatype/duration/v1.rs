@@ -0,0 +1,262 @@
+//! The first version of the Duration datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use super::SignedDuration;
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    util::secs_and_nanos_to_u128,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The duration in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the duration
+    #[serde(rename = "o")]
+    ore_ciphertext: Option<OREv1<12, 256>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// What's actually encrypted into the AES ciphertext
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredDuration {
+    /// Whole seconds
+    s: i64,
+    /// Nanoseconds on top of that
+    n: u32,
+}
+
+/// Identifier for the subkey used to encrypt the ORE ciphertext
+const DURATION_V1_ORE_KEY_IDENTIFIER: &[u8] = b"DurationV1.ore_key";
+
+impl V1 {
+    /// Encrypt the duration
+    pub(crate) fn new(d: SignedDuration, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(d, context, field, false)
+    }
+
+    /// Encrypt the duration in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        d: SignedDuration,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(d, context, field, true)
+    }
+
+    /// Encrypt the duration for decryption, and its signed length in nanoseconds for querying
+    ///
+    fn encrypt(
+        d: SignedDuration,
+        context: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
+        let s_d = StoredDuration {
+            s: d.secs(),
+            n: d.nanos(),
+        };
+
+        let v = cbor!(s_d).map_err(|e| {
+            Error::EncodingError(format!("failed to convert duration to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode duration value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(Self::ore(d, field, include_left)?),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt a duration into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore(
+        d: SignedDuration,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<12, 256>, Error> {
+        let u = secs_and_nanos_to_u128(d.secs(), d.nanos())?;
+
+        if include_left {
+            Ok(OREv1::<12, 256>::new_with_left(
+                u,
+                DURATION_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        } else {
+            Ok(OREv1::<12, 256>::new(
+                u,
+                DURATION_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        }
+    }
+
+    /// Turn the ciphertext back into a duration
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<SignedDuration, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let s_d = ciborium::de::from_reader::<StoredDuration, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        SignedDuration::new(s_d.s, s_d.n)
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Return the ORE ciphertext, if the duration hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<12, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.ore_ciphertext = None;
+    }
+
+    /// Compare two durations, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
+}
+
+impl Ord for V1 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V1 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn d(secs: i64, nanos: u32) -> SignedDuration {
+        SignedDuration::new(secs, nanos).unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V1::new(d(-2, 500_000_000), b"context", &field()).unwrap();
+
+        assert_eq!(
+            d(-2, 500_000_000),
+            value.decrypt(b"context", &field()).unwrap()
+        );
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(d(60, 0), b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(d(60, 0), b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn durations_compare_correctly() {
+        let values = [
+            d(i64::MIN, 0),
+            d(-2, 500_000_000),
+            d(-1, 0),
+            d(0, 0),
+            d(0, 1),
+            d(86_400, 0),
+            d(i64::MAX, 999_999_999),
+        ];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l:?} vs {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let value = V1::new_with_unsafe_parts(d(60, 0), b"ctx", &field()).unwrap();
+        let safe = V1::new(d(60, 0), b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::new(d(60, 0), b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
@@ -16,7 +16,7 @@ use crate::{
 };
 
 /// Versioning support
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[allow(clippy::missing_docs_in_private_items)] // Hopefully these names are fairly self-describing...
 enum Ciphertext<const N: usize, const W: u16> {
     #[allow(non_camel_case_types)]
@@ -40,7 +40,7 @@ enum Ciphertext<const N: usize, const W: u16> {
 /// If you start thinking that you'd like to start storing these somewhere in their own right, you
 /// probably want to be defining a separate standalone data type.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ERE<const N: usize, const W: u16> {
     /// The ERE ciphertext itself
     #[serde(rename = "e")]
@@ -91,22 +91,50 @@ impl<const N: usize, const W: u16> ERE<N, W> {
             kid: field.key_id()?.into(),
         })
     }
+
+    /// Create an ERE ciphertext from an `EREv1` ciphertext
+    ///
+    /// Much like [`ORE::from_ore_v1()`](crate::datatype::ORE::from_ore_v1), this "wraps" an
+    /// `EREv1` taken from inside some other datatype so it can be used as a value in its own right.
+    ///
+    #[must_use]
+    pub fn from_ere_v1(e: EREv1<N, W>, key_id: KeyId) -> ERE<N, W> {
+        ERE::<N, W> {
+            ere_ciphertext: Ciphertext::v1(e),
+            kid: key_id.into(),
+        }
+    }
+
+    /// Determine whether this ERE value is equal to another, returning an error if the comparison
+    /// can't be done
+    ///
+    /// The `PartialEq` implementation for `ERE` panics if the values cannot be compared, which
+    /// isn't ideal when one of them has come from somewhere you don't control.  This method tells
+    /// you what went wrong instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values were encrypted with different field keys, if neither value
+    /// contains a left ciphertext, or if either value is of an unknown version.
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        match (&self.ere_ciphertext, &other.ere_ciphertext) {
+            (Ciphertext::v1(s), Ciphertext::v1(o)) => s.try_eq(o),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
 }
 
 impl<const N: usize, const W: u16> PartialEq for ERE<N, W> {
     #[allow(clippy::panic)] // No way to signal error from impl PartialEq
     fn eq(&self, other: &Self) -> bool {
-        #[allow(clippy::match_wildcard_for_single_variants)]
-        // Actually, that is exactly what I *do* want here
-        match &self.ere_ciphertext {
-            Ciphertext::v1(s) => match &other.ere_ciphertext {
-                Ciphertext::v1(o) => s == o,
-                _ => panic!("Cannot compare a v1 ERE ciphertext with any other type of ciphertext"),
-            },
-            Ciphertext::Unknown => {
-                panic!("Cannot compare against an Unknown version ERE ciphertext")
-            }
-        }
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -167,6 +195,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn try_eq_reports_missing_left_ciphertext() {
+        let ca = ERE::<8, 16>::new(8u8, b"test", &field()).unwrap();
+        let cb = ERE::<8, 16>::new(8u8, b"test", &field()).unwrap();
+
+        assert!(matches!(
+            ca.try_eq(&cb),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+    }
+
     #[test]
     #[should_panic]
     fn need_one_left_ciphertext() {
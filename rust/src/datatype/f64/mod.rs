@@ -0,0 +1,280 @@
+//! An encrypted, yet queryable, double-precision floating-point number
+//!
+
+mod v1;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted, queryable 64-bit floating-point number
+///
+/// Encrypted floats sort in numeric order, from negative infinity up to positive infinity, with a
+/// couple of wrinkles to make them behave like the values in a database column, rather than
+/// IEEE754 floats:
+///
+/// * Negative zero and positive zero are equal to each other, just like they are in Rust; and
+/// * NaNs are equal to each other, and greater than every other value (including infinity),
+///   which is how Postgres sorts them.  Rust, on the other hand, says that NaN isn't equal
+///   to anything, not even itself, which is no good for sorting.
+///
+/// Decryption always gives back *exactly* the value that was encrypted, right down to the sign
+/// of a zero or the payload of a NaN.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum F64 {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl F64 {
+    /// Create a new encrypted, queryable float
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new(f: f64, context: &[u8], field: &Field) -> Result<F64, Error> {
+        Ok(F64::v1(Box::new(V1::new(f, context, field)?)))
+    }
+
+    /// Create a new encrypted, queryable float with degraded security
+    ///
+    /// While the value itself is securely encrypted, the ciphertexts produced by this function may
+    /// contain components that allow an attacker to infer, either precisely or approximately, the
+    /// plaintext value.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new_with_unsafe_parts(f: f64, context: &[u8], field: &Field) -> Result<F64, Error> {
+        Ok(F64::v1(Box::new(V1::new_with_unsafe_parts(
+            f, context, field,
+        )?)))
+    }
+
+    /// Decrypt the float
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the value could not be successfully decrypted, which may happen
+    /// because the wrong field was used, or because the decryption context was incorrect.  See
+    /// [`F64::new()`](F64::new) for more details about encryption and decryption contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<f64, Error> {
+        match self {
+            F64::v1(f) => f.decrypt(context, field),
+            F64::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Compare this float with another, without panicking if it can't be done
+    ///
+    /// See [the type documentation](F64) for how zeroes and NaNs are ordered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the floats were encrypted with different field keys, if either has
+    /// been made unqueryable, if neither has the left ciphertext required to perform the
+    /// comparison, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (F64::v1(s), F64::v1(o)) => s.try_cmp(o),
+            (F64::Unknown, _) | (_, F64::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this float is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`F64::try_cmp()`](F64::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored floats
+    ///
+    /// This is what you need for range queries, like `WHERE temperature > 37.5`.  The value is
+    /// encrypted with the field's ORE key, and the result is suitable for passing to
+    /// [`F64::try_cmp_query()`](F64::try_cmp_query) for any float encrypted using the same field,
+    /// regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query(f: f64, field: &Field) -> Result<Kith<ORE<8, 256>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(V1::ore(f, field, true)?, field.key_id()?));
+        Ok(k)
+    }
+
+    /// Compare this float with the value represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by [`F64::query()`](F64::query), using
+    /// the same field as this float.  The result says where this float sorts relative to the
+    /// queried value, so `Ordering::Greater` means that this float would satisfy `f > query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the float has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this float, or if the float is of an unknown version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<ORE<8, 256>>) -> Result<Ordering, Error> {
+        let ore = match self {
+            F64::v1(f) => f
+                .ore_ciphertext()
+                .map(|o| ORE::from_ore_v1(o.clone(), f.key_id())),
+            F64::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(ore, ORE::try_cmp)
+    }
+
+    /// Remove the ability to perform any queries on this encrypted value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted number, without any ability
+    /// to query it.  In that case, you can save some space by calling this method before
+    /// serialisation.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if for some unfathomable reason the ciphertext cannot be made
+    /// unqueryable, such as if an attempt is made to make an Unknown version ciphertext
+    /// unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            F64::v1(f) => {
+                f.make_unqueryable();
+                Ok(())
+            }
+            F64::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl Ord for F64 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for F64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for F64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for F64 {}
+
+impl KithDatatype for F64 {
+    fn key_id(&self) -> KeyId {
+        match self {
+            F64::v1(f) => f.key_id(),
+            F64::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            F64::v1(_) => 1,
+            F64::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn negative_numbers_sort_in_order() {
+        let value = F64::new(-1.5, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            value
+                .try_cmp_query(&F64::query(-273.15, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            value
+                .try_cmp_query(&F64::query(-1.25, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            value
+                .try_cmp_query(&F64::query(f64::MIN_POSITIVE, &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn nans_sort_last() {
+        let nan = F64::new(f64::NAN, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            nan.try_cmp_query(&F64::query(f64::INFINITY, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            nan.try_cmp_query(&F64::query(-f64::NAN, &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn zeroes_are_equal() {
+        let negative = F64::new_with_unsafe_parts(-0.0, b"row 1", &field()).unwrap();
+        let positive = F64::new(0.0, b"row 2", &field()).unwrap();
+
+        assert!(negative == positive);
+        assert!(negative
+            .decrypt(b"row 1", &field())
+            .unwrap()
+            .is_sign_negative());
+    }
+}
@@ -0,0 +1,294 @@
+//! The first version of the F64 datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The number in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the number
+    #[serde(rename = "o")]
+    ore_ciphertext: Option<OREv1<8, 256>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// Identifier for the subkey used to encrypt the ORE ciphertext
+const F64_V1_ORE_KEY_IDENTIFIER: &[u8] = b"F64V1.ore_key";
+
+/// The bit of an IEEE754 double that says whether it's negative
+const SIGN_BIT: u64 = 0x8000_0000_0000_0000;
+
+/// The bits of the one true (positive, quiet) NaN
+const CANONICAL_NAN: u64 = 0x7ff8_0000_0000_0000;
+
+/// Turn a double into a u64 that sorts the same way
+///
+/// Positive numbers sort correctly if you treat their bits as an unsigned integer, as long as
+/// you set the sign bit so they sort above all the negative numbers.  Negative numbers sort
+/// backwards, so all their bits get flipped.  Before that, though, negative zero is turned into
+/// positive zero, so the two compare equal, and every NaN is turned into the same positive NaN,
+/// which ends up sorting above positive infinity.
+///
+fn orderable_bits(f: f64) -> u64 {
+    let bits = if f.is_nan() {
+        CANONICAL_NAN
+    } else if f.to_bits() & !SIGN_BIT == 0 {
+        0
+    } else {
+        f.to_bits()
+    };
+
+    if bits & SIGN_BIT == 0 {
+        bits | SIGN_BIT
+    } else {
+        !bits
+    }
+}
+
+impl V1 {
+    /// Encrypt the number
+    pub(crate) fn new(f: f64, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(f, context, field, false)
+    }
+
+    /// Encrypt the number in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        f: f64,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(f, context, field, true)
+    }
+
+    /// Encrypt the number for decryption, and its order-preserving bits for querying
+    ///
+    /// The number is stored as its raw bits, rather than as a CBOR float, so that every last
+    /// detail (the sign of a zero, the payload of a NaN) comes back out exactly as it went in.
+    ///
+    fn encrypt(f: f64, context: &[u8], field: &Field, include_left: bool) -> Result<V1, Error> {
+        let v = cbor!(f.to_bits()).map_err(|e| {
+            Error::EncodingError(format!("failed to convert f64 to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode f64 value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(Self::ore(f, field, include_left)?),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt a number into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore(f: f64, field: &Field, include_left: bool) -> Result<OREv1<8, 256>, Error> {
+        let u = orderable_bits(f);
+
+        if include_left {
+            Ok(OREv1::<8, 256>::new_with_left(
+                u,
+                F64_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        } else {
+            Ok(OREv1::<8, 256>::new(u, F64_V1_ORE_KEY_IDENTIFIER, field)?)
+        }
+    }
+
+    /// Turn the ciphertext back into a number
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<f64, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let bits = ciborium::de::from_reader::<u64, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        Ok(f64::from_bits(bits))
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Return the ORE ciphertext, if the number hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<8, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.ore_ciphertext = None;
+    }
+
+    /// Compare two numbers, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
+}
+
+impl Ord for V1 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V1 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn values_round_trip_exactly() {
+        let weird_nan = f64::from_bits(0xfff0_0000_dead_beef);
+
+        for f in [0.0, -0.0, 1.5, -1e300, f64::INFINITY, f64::NAN, weird_nan] {
+            let value = V1::new(f, b"context", &field()).unwrap();
+
+            assert_eq!(
+                f.to_bits(),
+                value.decrypt(b"context", &field()).unwrap().to_bits()
+            );
+        }
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(1.0, b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(1.0, b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn orderable_bits_sort_correctly() {
+        let values = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1.0,
+            -f64::MIN_POSITIVE,
+            -f64::from_bits(1),
+            0.0,
+            f64::from_bits(1),
+            f64::MIN_POSITIVE,
+            1.0,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+
+        for pair in values.windows(2) {
+            assert!(
+                orderable_bits(pair[0]) < orderable_bits(pair[1]),
+                "{} vs {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn zeroes_and_nans_are_equal_to_their_own_kind() {
+        assert_eq!(orderable_bits(0.0), orderable_bits(-0.0));
+        assert_eq!(
+            orderable_bits(f64::NAN),
+            orderable_bits(f64::from_bits(0xfff0_0000_dead_beef))
+        );
+    }
+
+    #[test]
+    fn numbers_compare_correctly() {
+        let values = [f64::NEG_INFINITY, -2.5, -0.0, 1e-300, 42.0, f64::NAN];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l} vs {r}");
+            }
+        }
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let value = V1::new_with_unsafe_parts(1.0, b"ctx", &field()).unwrap();
+        let safe = V1::new(1.0, b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::new(1.0, b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
@@ -2,14 +2,21 @@
 //!
 
 mod v1;
+mod v2;
 
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
-use self::v1::V1;
-use crate::{datatype::kith::Datatype as KithDatatype, field::KeyId, Error, Field};
+use self::{v1::V1, v2::V2};
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
 
 /// Signed 64-bit encrypted integer
-#[derive(Debug, Serialize, Deserialize, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(
     clippy::large_enum_variant,
     missing_docs,
@@ -19,6 +26,8 @@ use crate::{datatype::kith::Datatype as KithDatatype, field::KeyId, Error, Field
 pub enum I64 {
     #[allow(non_camel_case_types)]
     v1(V1),
+    #[allow(non_camel_case_types)]
+    v2(V2),
     Unknown,
 }
 
@@ -32,7 +41,7 @@ impl I64 {
     /// Can return an error if the process of encrypting the data fails.
     ///
     pub fn new(i: i64, context: &[u8], field: &Field) -> Result<I64, Error> {
-        Ok(I64::v1(V1::new(i, context, field)?))
+        Ok(I64::v2(V2::new(i, context, field)?))
     }
 
     /// Create a new encrypted, queryable signed 64-bit integer with degraded security
@@ -50,7 +59,7 @@ impl I64 {
     /// Can return an error if the process of encrypting the data fails.
     ///
     pub fn new_with_unsafe_parts(i: i64, context: &[u8], field: &Field) -> Result<I64, Error> {
-        Ok(I64::v1(V1::new_with_unsafe_parts(i, context, field)?))
+        Ok(I64::v2(V2::new_with_unsafe_parts(i, context, field)?))
     }
 
     /// Extract the value of the integer from the ciphertext
@@ -67,6 +76,120 @@ impl I64 {
     pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<i64, Error> {
         match self {
             I64::v1(i) => i.decrypt(context, field),
+            I64::v2(i) => i.decrypt(context, field),
+            I64::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Compare this integer with another, without panicking if it can't be done
+    ///
+    /// The `Ord` implementation for `I64` has no way to report a problem other than panicking,
+    /// which is no fun at all when the value has come out of a database you don't entirely trust.
+    ///
+    /// Integers of different versions are encrypted with different keys, and so can't be
+    /// compared; [upgrade](I64::upgrade) the older one first.  Bear in mind that version 1
+    /// integers can only be compared with other version 1 integers that were encrypted with the
+    /// same context, and there's no way to tell if they weren't -- the comparison just gives a
+    /// meaningless answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the integers were encrypted with different field keys, if either has
+    /// been made unqueryable, if neither has the left ciphertext required to perform the
+    /// comparison, if the integers are of different versions, or if either is of an unknown
+    /// version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (I64::v1(s), I64::v1(o)) => s.try_cmp(o),
+            (I64::v2(s), I64::v2(o)) => s.try_cmp(o),
+            (I64::Unknown, _) | (_, I64::Unknown) => Err(Error::UnknownVersionError()),
+            (s, o) => Err(Error::VersionMismatchError(format!(
+                "v{} vs v{}",
+                s.ciphertext_version(),
+                o.ciphertext_version()
+            ))),
+        }
+    }
+
+    /// Determine whether this integer is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`I64::try_cmp()`](I64::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored integers
+    ///
+    /// This is what you need for range queries, like `WHERE age >= 18`.  The value is encrypted
+    /// with the field's ORE key, and the result is suitable for passing to
+    /// [`I64::try_cmp_query()`](I64::try_cmp_query) for any integer encrypted using the same
+    /// field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query(i: i64, field: &Field) -> Result<Kith<ORE<8, 256>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(V2::ore_query(i, field)?, field.key_id()?));
+        Ok(k)
+    }
+
+    /// Compare this integer with the value represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by [`I64::query()`](I64::query), using
+    /// the same field as this integer.  The result says where this integer sorts relative to the
+    /// queried value, so `Ordering::Greater` means that this integer would satisfy `i > query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the integer has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this integer, or if the integer is a version 1 value
+    /// (which can't be queried, because its ORE key depends on its encryption context) or of an
+    /// unknown version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<ORE<8, 256>>) -> Result<Ordering, Error> {
+        let ore = match self {
+            I64::v1(_) => {
+                return Err(Error::VersionMismatchError(
+                    "v1 integers cannot be queried, upgrade them first".to_string(),
+                ))
+            }
+            I64::v2(i) => i
+                .ore_ciphertext()
+                .map(|o| ORE::from_ore_v1(o.clone(), i.key_id())),
+            I64::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(ore, ORE::try_cmp)
+    }
+
+    /// Convert this integer to the latest version
+    ///
+    /// Older versions of `I64` remain readable, but new integers are always created with the
+    /// latest version, and integers of different versions can't be compared against each other.
+    /// Upgrading re-encrypts the integer, so the `context` must be the same one that was used when
+    /// it was created.  The upgraded integer only has unsafe parts if the original did, and
+    /// unqueryable integers stay unqueryable.
+    ///
+    /// Integers which are already the latest version are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the integer could not be decrypted, if the encryption process
+    /// fails, or if the integer is of an unknown version.
+    ///
+    pub fn upgrade(&mut self, context: &[u8], field: &Field) -> Result<(), Error> {
+        match self {
+            I64::v1(i) => {
+                *self = I64::v2(V2::from_v1(i, context, field)?);
+                Ok(())
+            }
+            I64::v2(_) => Ok(()),
             I64::Unknown => Err(Error::UnknownVersionError()),
         }
     }
@@ -89,15 +212,41 @@ impl I64 {
                 i.make_unqueryable();
                 Ok(())
             }
+            I64::v2(i) => {
+                i.make_unqueryable();
+                Ok(())
+            }
             I64::Unknown => Err(Error::UnknownVersionError()),
         }
     }
 }
 
+impl Ord for I64 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for I64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for I64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for I64 {}
+
 impl KithDatatype for I64 {
     fn key_id(&self) -> KeyId {
         match self {
             I64::v1(i) => i.key_id(),
+            I64::v2(i) => i.key_id(),
             I64::Unknown => Default::default(),
         }
     }
@@ -105,7 +254,94 @@ impl KithDatatype for I64 {
     fn ciphertext_version(&self) -> u32 {
         match self {
             I64::v1(_) => 1,
+            I64::v2(_) => 2,
             I64::Unknown => 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn range_query() {
+        let value = I64::new(42, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            value
+                .try_cmp_query(&I64::query(-1, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            value
+                .try_cmp_query(&I64::query(42, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            value
+                .try_cmp_query(&I64::query(i64::MAX, &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn v1_values_cannot_be_queried() {
+        let value = I64::v1(V1::new(42, b"row 1", &field()).unwrap());
+
+        assert!(matches!(
+            value.try_cmp_query(&I64::query(42, &field()).unwrap()),
+            Err(Error::VersionMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn mixed_versions_cannot_be_compared() {
+        let v1 = I64::v1(V1::new_with_unsafe_parts(42, b"row 1", &field()).unwrap());
+        let v2 = I64::new_with_unsafe_parts(42, b"row 1", &field()).unwrap();
+
+        assert!(matches!(
+            v1.try_cmp(&v2),
+            Err(Error::VersionMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn upgraded_values_can_be_compared_across_contexts() {
+        let mut old = I64::v1(V1::new(42, b"row 1", &field()).unwrap());
+        old.upgrade(b"row 1", &field()).unwrap();
+
+        assert_eq!(2, old.ciphertext_version());
+        assert_eq!(42, old.decrypt(b"row 1", &field()).unwrap());
+        assert_eq!(
+            Ordering::Less,
+            old.try_cmp(&I64::new_with_unsafe_parts(69, b"row 2", &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn serialised_form_is_unchanged_apart_from_the_version() {
+        let value = I64::new(42, b"row 1", &field()).unwrap();
+        let json: serde_json::Value = serde_json::to_value(&value).unwrap();
+
+        let v2 = json.get("v2").unwrap();
+        assert!(v2.get("a").is_some());
+        assert!(v2.get("o").is_some());
+        assert!(v2.get("k").is_some());
+    }
+}
@@ -37,25 +37,40 @@ pub struct V1 {
 const I64_OFFSET: i128 = 0x8000_0000_0000_0000;
 
 impl V1 {
+    // New integers are always created as the latest version, so these constructors only exist so
+    // that the tests can get their hands on a complete V1 value.
+
     /// Create an encrypted bigint
     ///
+    #[cfg(test)]
     pub(crate) fn new(i: i64, context: &[u8], field: &Field) -> Result<V1, Error> {
-        Self::encrypt(i, context, field, false)
+        Self::encrypt(i, context, context, field, false)
     }
 
     /// Create an encrypted bigint with lower security guarantees
     ///
+    #[cfg(test)]
     pub(crate) fn new_with_unsafe_parts(
         i: i64,
         context: &[u8],
         field: &Field,
     ) -> Result<V1, Error> {
-        Self::encrypt(i, context, field, true)
+        Self::encrypt(i, context, context, field, true)
     }
 
     /// Do the hard yards of actually poking the cryptography and assembling the struct
     ///
-    fn encrypt(i: i64, context: &[u8], field: &Field, include_left: bool) -> Result<V1, Error> {
+    /// Version 1 values use the encryption context as the identifier of the ORE subkey, which
+    /// means that only values encrypted with the same context can be compared.  Later versions
+    /// pass in something else.
+    ///
+    pub(crate) fn encrypt(
+        i: i64,
+        context: &[u8],
+        ore_subkey_id: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
         let v = cbor!(i).map_err(|e| {
             Error::EncodingError(format!("failed to convert i64 to ciborium value: {e}"))
         })?;
@@ -66,6 +81,24 @@ impl V1 {
 
         let aes = AES256v1::new(&msg, context, field)?;
 
+        let ore = Self::ore(i, ore_subkey_id, field, include_left)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(ore),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt an i64 into an order-revealing ciphertext, using the field subkey named by
+    /// `subkey_id`
+    ///
+    pub(crate) fn ore(
+        i: i64,
+        subkey_id: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<8, 256>, Error> {
         let u: u64 = (check_overflow(
             i128::from(i).overflowing_add(I64_OFFSET),
             "while offsetting i64",
@@ -73,17 +106,11 @@ impl V1 {
         .try_into()
         .map_err(|e| Error::EncodingError(format!("failed to convert i64 {i} to u64 ({e})")))?;
 
-        let ore = if include_left {
-            OREv1::<8, 256>::new_with_left(u, context, field)?
+        if include_left {
+            Ok(OREv1::<8, 256>::new_with_left(u, subkey_id, field)?)
         } else {
-            OREv1::<8, 256>::new(u, context, field)?
-        };
-
-        Ok(V1 {
-            aes_ciphertext: aes,
-            ore_ciphertext: Some(ore),
-            kid: field.key_id()?.into(),
-        })
+            Ok(OREv1::<8, 256>::new(u, subkey_id, field)?)
+        }
     }
 
     /// Do the decryption
@@ -112,30 +139,37 @@ impl V1 {
         key_id
     }
 
+    /// Return the ciphertext that makes the "queryable" bit work, if it's still there
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<8, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
     /// Strip out everything that makes the "queryable" bit work
     pub(crate) fn make_unqueryable(&mut self) {
         self.ore_ciphertext = None;
     }
+
+    /// Compare two ciphertexts, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
 }
 
 impl Ord for V1 {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to signal error from impl Ord
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        assert!(
-            self.kid == other.kid,
-            "Cannot compare ciphertexts from different keys"
-        );
-
-        let lhs = self
-            .ore_ciphertext
-            .as_ref()
-            .expect("Cannot compare without an ORE ciphertext on the left-hand side");
-        let rhs = other
-            .ore_ciphertext
-            .as_ref()
-            .expect("Cannot compare without an ORE ciphertext on the right-hand side");
-
-        lhs.cmp(rhs)
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -199,4 +233,34 @@ mod tests {
 
         assert!(!value.ore_ciphertext.unwrap().has_left());
     }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let other_field = Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"baz")
+        .unwrap();
+
+        let value = V1::new_with_unsafe_parts(42, b"somecontext", &field()).unwrap();
+        let other_key = V1::new_with_unsafe_parts(42, b"somecontext", &other_field).unwrap();
+        let safe = V1::new(42, b"somecontext", &field()).unwrap();
+        let mut unqueryable = V1::new(42, b"somecontext", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            value.try_cmp(&other_key),
+            Err(Error::KeyMismatchError())
+        ));
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert_eq!(Ordering::Equal, value.try_cmp(&safe).unwrap());
+    }
 }
@@ -0,0 +1,181 @@
+//! Version 2 of the queryable encrypted signed 64-bit integer
+//!
+//! Version 1 used the encryption context to derive the ORE key, which made it impossible to
+//! compare values that were encrypted with different contexts -- which is to say, pretty much any
+//! two values in a table, if you followed the advice on choosing contexts.  Version 2 derives the
+//! ORE key from a fixed, field-scoped identifier instead, and is otherwise identical.
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::v1::V1;
+use crate::{crypto::OREv1, field::KeyId, Error, Field};
+
+/// The data itself
+///
+/// The serialised form is exactly the same as version 1; it's only the key used for the ORE
+/// ciphertext that differs.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+#[doc(hidden)]
+pub struct V2 {
+    /// The ciphertexts, which are the same shape as they ever were
+    base: V1,
+}
+
+/// Identifier for the subkey used to encrypt the ORE ciphertext
+const I64_V2_ORE_KEY_IDENTIFIER: &[u8] = b"I64V2.ore_key";
+
+impl V2 {
+    /// Create an encrypted bigint
+    ///
+    pub(crate) fn new(i: i64, context: &[u8], field: &Field) -> Result<V2, Error> {
+        Ok(V2 {
+            base: V1::encrypt(i, context, I64_V2_ORE_KEY_IDENTIFIER, field, false)?,
+        })
+    }
+
+    /// Create an encrypted bigint with lower security guarantees
+    ///
+    pub(crate) fn new_with_unsafe_parts(
+        i: i64,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V2, Error> {
+        Ok(V2 {
+            base: V1::encrypt(i, context, I64_V2_ORE_KEY_IDENTIFIER, field, true)?,
+        })
+    }
+
+    /// Re-encrypt a v1 value as v2
+    ///
+    /// The new value only includes a left ciphertext if the old one did, and a value which has
+    /// been made unqueryable stays that way.
+    ///
+    pub(crate) fn from_v1(v1: &V1, context: &[u8], field: &Field) -> Result<V2, Error> {
+        let i = v1.decrypt(context, field)?;
+
+        match v1.ore_ciphertext() {
+            Some(ore) if ore.has_left() => Self::new_with_unsafe_parts(i, context, field),
+            Some(_) => Self::new(i, context, field),
+            None => {
+                let mut v2 = Self::new(i, context, field)?;
+                v2.make_unqueryable();
+                Ok(v2)
+            }
+        }
+    }
+
+    /// Encrypt a value into an ORE ciphertext that can be compared against v2 values
+    ///
+    pub(crate) fn ore_query(i: i64, field: &Field) -> Result<OREv1<8, 256>, Error> {
+        V1::ore(i, I64_V2_ORE_KEY_IDENTIFIER, field, true)
+    }
+
+    /// Do the decryption
+    ///
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<i64, Error> {
+        self.base.decrypt(context, field)
+    }
+
+    /// Return the ciphertext's field key ID, in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        self.base.key_id()
+    }
+
+    /// Return the ORE ciphertext, if the value hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<8, 256>> {
+        self.base.ore_ciphertext()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.base.make_unqueryable();
+    }
+
+    /// Compare two ciphertexts, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        self.base.try_cmp(&other.base)
+    }
+}
+
+impl Ord for V2 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V2 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V2 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V2 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V2::new(42, b"context", &field()).unwrap();
+
+        assert_eq!(42, value.decrypt(b"context", &field()).unwrap());
+    }
+
+    #[test]
+    fn values_with_different_contexts_compare_correctly() {
+        let forty_two = V2::new_with_unsafe_parts(42, b"row 1", &field()).unwrap();
+        let sixty_nine = V2::new(69, b"row 2", &field()).unwrap();
+
+        assert_eq!(Ordering::Less, forty_two.try_cmp(&sixty_nine).unwrap());
+        assert_eq!(Ordering::Greater, sixty_nine.try_cmp(&forty_two).unwrap());
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V2::new(42, b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn upgrade_preserves_safety() {
+        let safe = V2::from_v1(&V1::new(42, b"ctx", &field()).unwrap(), b"ctx", &field()).unwrap();
+        let not_safe = V2::from_v1(
+            &V1::new_with_unsafe_parts(42, b"ctx", &field()).unwrap(),
+            b"ctx",
+            &field(),
+        )
+        .unwrap();
+        let mut unqueryable = V1::new(42, b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+        let unqueryable = V2::from_v1(&unqueryable, b"ctx", &field()).unwrap();
+
+        assert!(!safe.ore_ciphertext().unwrap().has_left());
+        assert!(not_safe.ore_ciphertext().unwrap().has_left());
+        assert!(unqueryable.ore_ciphertext().is_none());
+        assert_eq!(42, unqueryable.decrypt(b"ctx", &field()).unwrap());
+    }
+}
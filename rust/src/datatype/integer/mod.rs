@@ -0,0 +1,473 @@
+//! Encrypted, yet queryable, fixed-width integers
+//!
+//! Apart from how wide they are, and whether they can be negative, there isn't a lot of
+//! difference between one encrypted integer and another, so rather than copy-and-paste the same
+//! code for every one of them, they all use the [`V1`] ciphertext in here, specialised by the
+//! plaintext type and the number of bytes in the ORE ciphertext, and the public types wrapped
+//! around it are all stamped out by [`integer_datatype!`].
+//!
+
+mod v1;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    util::i128_to_orderable_u128,
+    Error, Field,
+};
+
+/// An integer type that knows how to become an ORE plaintext that sorts the same way it does
+///
+pub(crate) trait Integer: Copy + fmt::Display + Serialize + DeserializeOwned {
+    /// The unsigned type of the same width, which `cretrit` knows how to turn into a `PlainText`
+    type Orderable;
+
+    /// Identifier for the subkey used to encrypt ORE ciphertexts of this type
+    const ORE_KEY_IDENTIFIER: &'static [u8];
+
+    /// Map the value onto its unsigned equivalent, such that the ordering is preserved
+    ///
+    /// For unsigned types, this is nothing at all.  For signed types, it's the same as adding
+    /// `2^(bits - 1)`, which can be done without any risk of overflow by flipping the sign bit.
+    ///
+    fn orderable(self) -> Self::Orderable;
+}
+
+impl Integer for u128 {
+    type Orderable = u128;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"U128V1.ore_key";
+
+    fn orderable(self) -> u128 {
+        self
+    }
+}
+
+impl Integer for i128 {
+    type Orderable = u128;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"I128V1.ore_key";
+
+    fn orderable(self) -> u128 {
+        i128_to_orderable_u128(self)
+    }
+}
+
+impl Integer for u64 {
+    type Orderable = u64;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"U64V1.ore_key";
+
+    fn orderable(self) -> u64 {
+        self
+    }
+}
+
+impl Integer for i32 {
+    type Orderable = u32;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"I32V1.ore_key";
+
+    fn orderable(self) -> u32 {
+        u32::from_be_bytes(self.to_be_bytes()) ^ 0x8000_0000
+    }
+}
+
+impl Integer for i16 {
+    type Orderable = u16;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"I16V1.ore_key";
+
+    fn orderable(self) -> u16 {
+        u16::from_be_bytes(self.to_be_bytes()) ^ 0x8000
+    }
+}
+
+impl Integer for u8 {
+    type Orderable = u8;
+    const ORE_KEY_IDENTIFIER: &'static [u8] = b"U8V1.ore_key";
+
+    fn orderable(self) -> u8 {
+        self
+    }
+}
+
+/// Define a public encrypted integer type, wrapped around a [`V1`] of the given width
+///
+/// The type is named `$name`, encrypts `$t`s into `N = $n` byte ORE ciphertexts, and is
+/// described in its docs as a "`$desc` integer", with `$example` being the sort of `WHERE`
+/// clause a query of it might be used for.
+///
+macro_rules! integer_datatype {
+    ($(#[$attr:meta])* $name:ident($t:ty, $n:literal), $desc:literal, $example:literal) => {
+        $(#[$attr])*
+        #[derive(Debug, Serialize, Deserialize)]
+        #[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+        #[non_exhaustive]
+        pub enum $name {
+            #[allow(non_camel_case_types)]
+            v1(Box<V1<$t, $n>>),
+            Unknown,
+        }
+
+        impl $name {
+            #[doc = concat!("Create a new encrypted, queryable ", $desc, " integer")]
+            ///
+            #[doc = include_str!("../encryption_contexts.md")]
+            ///
+            /// # Errors
+            ///
+            /// Can return an error if the process of encrypting the data fails.
+            ///
+            pub fn new(i: $t, context: &[u8], field: &Field) -> Result<Self, Error> {
+                Ok(Self::v1(Box::new(V1::new(i, context, field)?)))
+            }
+
+            #[doc = concat!(
+                "Create a new encrypted, queryable ", $desc, " integer with degraded security"
+            )]
+            ///
+            /// While the value itself is securely encrypted, the ciphertexts produced by this
+            /// function may contain components that allow an attacker to infer, either precisely
+            /// or approximately, the plaintext value.
+            ///
+            /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+            ///
+            #[doc = include_str!("../encryption_contexts.md")]
+            ///
+            /// # Errors
+            ///
+            /// Can return an error if the process of encrypting the data fails.
+            ///
+            pub fn new_with_unsafe_parts(
+                i: $t,
+                context: &[u8],
+                field: &Field,
+            ) -> Result<Self, Error> {
+                Ok(Self::v1(Box::new(V1::new_with_unsafe_parts(
+                    i, context, field,
+                )?)))
+            }
+
+            /// Extract the value of the integer from the ciphertext
+            ///
+            /// # Errors
+            ///
+            /// Can return an error if the value could not be successfully decrypted, which may
+            /// happen because the wrong field was used, or because the decryption context was
+            #[doc = concat!(
+                "incorrect.  See [`", stringify!($name), "::new()`](", stringify!($name),
+                "::new) for more details about encryption and decryption contexts."
+            )]
+            ///
+            pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<$t, Error> {
+                match self {
+                    Self::v1(i) => i.decrypt(context, field),
+                    Self::Unknown => Err(Error::UnknownVersionError()),
+                }
+            }
+
+            /// Compare this integer with another, without panicking if it can't be done
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the integers were encrypted with different field keys, if
+            /// either has been made unqueryable, if neither has the left ciphertext required to
+            /// perform the comparison, or if either is of an unknown version.
+            ///
+            pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+                match (self, other) {
+                    (Self::v1(s), Self::v1(o)) => s.try_cmp(o),
+                    (Self::Unknown, _) | (_, Self::Unknown) => Err(Error::UnknownVersionError()),
+                }
+            }
+
+            /// Determine whether this integer is equal to another, without panicking if it can't
+            /// be done
+            ///
+            /// # Errors
+            ///
+            #[doc = concat!(
+                "Returns an error in all the same situations as [`", stringify!($name),
+                "::try_cmp()`](", stringify!($name), "::try_cmp)."
+            )]
+            ///
+            pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+                Ok(self.try_cmp(other)? == Ordering::Equal)
+            }
+
+            /// Generate a set of encrypted values suitable for comparing against stored integers
+            ///
+            #[doc = concat!(
+                "This is what you need for range queries, like `", $example, "`.  The value is ",
+                "encrypted with the field's ORE key, and the result is suitable for passing to ",
+                "[`", stringify!($name), "::try_cmp_query()`](", stringify!($name),
+                "::try_cmp_query) for any integer encrypted using the same field, regardless of ",
+                "the context it was encrypted with."
+            )]
+            ///
+            /// # Errors
+            ///
+            /// Can return an error if the encryption process fails.
+            ///
+            pub fn query(i: $t, field: &Field) -> Result<Kith<ORE<$n, 256>>, Error> {
+                let mut k = Kith::new();
+                k.add_member(ORE::from_ore_v1(V1::ore(i, field, true)?, field.key_id()?));
+                Ok(k)
+            }
+
+            /// Compare this integer with the value represented by a query
+            ///
+            #[doc = concat!(
+                "The `query` is a set of encrypted values produced by [`", stringify!($name),
+                "::query()`](", stringify!($name), "::query), using the same field as this ",
+                "integer.  The result says where this integer sorts relative to the queried ",
+                "value, so `Ordering::Greater` means that this integer would satisfy ",
+                "`i > query`."
+            )]
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the integer has been made unqueryable, if the query does not
+            /// contain a value which can be compared against this integer, or if the integer is
+            /// of an unknown version.
+            ///
+            pub fn try_cmp_query(&self, query: &Kith<ORE<$n, 256>>) -> Result<Ordering, Error> {
+                let ore = match self {
+                    Self::v1(i) => i
+                        .ore_ciphertext()
+                        .map(|o| ORE::from_ore_v1(o.clone(), i.key_id())),
+                    Self::Unknown => return Err(Error::UnknownVersionError()),
+                };
+
+                query.compare_with(ore, ORE::try_cmp)
+            }
+
+            /// Remove the ability to perform any queries on this encrypted value
+            ///
+            /// Sometimes you just want to be able to store a safely encrypted number, without
+            /// any ability to query it.  In that case, you can save some space by calling this
+            /// method before serialisation.
+            ///
+            /// # Errors
+            ///
+            /// Can return an error if for some unfathomable reason the ciphertext cannot be made
+            /// unqueryable, such as if an attempt is made to make an Unknown version ciphertext
+            /// unqueryable.
+            ///
+            pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+                match self {
+                    Self::v1(i) => {
+                        i.make_unqueryable();
+                        Ok(())
+                    }
+                    Self::Unknown => Err(Error::UnknownVersionError()),
+                }
+            }
+        }
+
+        impl Ord for $name {
+            #[allow(clippy::panic)] // No way to signal error from impl Ord
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+            }
+        }
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl KithDatatype for $name {
+            fn key_id(&self) -> KeyId {
+                match self {
+                    Self::v1(i) => i.key_id(),
+                    Self::Unknown => Default::default(),
+                }
+            }
+
+            fn ciphertext_version(&self) -> u32 {
+                match self {
+                    Self::v1(_) => 1,
+                    Self::Unknown => 0,
+                }
+            }
+        }
+    };
+}
+
+integer_datatype! {
+    /// Signed 128-bit encrypted integer
+    ///
+    /// For ledger amounts and other numbers that won't fit in an [`I64`](crate::datatype::I64).
+    /// The ORE ciphertext is twice the size of an `I64`'s, so only use this if you need the range.
+    ///
+    I128(i128, 16), "signed 128-bit", "WHERE balance < 0"
+}
+
+integer_datatype! {
+    /// Unsigned 128-bit encrypted integer
+    ///
+    /// Big enough for identifiers that are a 128-bit number, with an ORE ciphertext that is twice
+    /// the size of a [`U64`](crate::datatype::U64)'s.
+    ///
+    U128(u128, 16), "unsigned 128-bit", "WHERE id > 1000"
+}
+
+integer_datatype! {
+    /// Unsigned 64-bit encrypted integer
+    ///
+    U64(u64, 8), "unsigned 64-bit", "WHERE views > 1000"
+}
+
+integer_datatype! {
+    /// Signed 32-bit encrypted integer
+    ///
+    /// Half the size of an [`I64`](crate::datatype::I64), for when you don't need the range.
+    ///
+    I32(i32, 4), "signed 32-bit", "WHERE quantity < 0"
+}
+
+integer_datatype! {
+    /// Signed 16-bit encrypted integer
+    ///
+    /// Good for small counts, years, and the like, with a quarter of the ORE ciphertext of an
+    /// [`I64`](crate::datatype::I64).
+    ///
+    I16(i16, 2), "signed 16-bit", "WHERE floor < -1"
+}
+
+integer_datatype! {
+    /// Unsigned 8-bit encrypted integer
+    ///
+    /// For things like ages, ratings, and other small, non-negative numbers, this has the smallest
+    /// ciphertext of any of the integer types.
+    ///
+    U8(u8, 1), "unsigned 8-bit", "WHERE age >= 18"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn signed_integers_keep_their_order() {
+        let i128s = [i128::MIN, i128::from(i64::MIN), -1, 0, 1, i128::MAX];
+        for pair in i128s.windows(2) {
+            assert!(pair[0].orderable() < pair[1].orderable(), "{pair:?}");
+        }
+
+        let i32s = [i32::MIN, -1, 0, 1, i32::MAX];
+        for pair in i32s.windows(2) {
+            assert!(pair[0].orderable() < pair[1].orderable(), "{pair:?}");
+        }
+
+        let i16s = [i16::MIN, -1, 0, 1, i16::MAX];
+        for pair in i16s.windows(2) {
+            assert!(pair[0].orderable() < pair[1].orderable(), "{pair:?}");
+        }
+    }
+
+    #[test]
+    fn signed_integers_use_the_whole_range() {
+        assert_eq!(0, i128::MIN.orderable());
+        assert_eq!(u128::MAX, i128::MAX.orderable());
+        assert_eq!(0, i32::MIN.orderable());
+        assert_eq!(u32::MAX, i32::MAX.orderable());
+        assert_eq!(0, i16::MIN.orderable());
+        assert_eq!(u16::MAX, i16::MAX.orderable());
+    }
+
+    #[test]
+    fn extremes_round_trip() {
+        for i in [i128::MIN, i128::MAX] {
+            let value = I128::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+        for i in [u128::MIN, u128::MAX] {
+            let value = U128::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+        for i in [u64::MIN, u64::MAX] {
+            let value = U64::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+        for i in [i32::MIN, i32::MAX] {
+            let value = I32::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+        for i in [i16::MIN, i16::MAX] {
+            let value = I16::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+        for i in [u8::MIN, u8::MAX] {
+            let value = U8::new(i, b"row 1", &field()).unwrap();
+            assert_eq!(i, value.decrypt(b"row 1", &field()).unwrap());
+        }
+    }
+
+    #[test]
+    fn range_query() {
+        let value = I32::new(1_000_000, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            value
+                .try_cmp_query(&I32::query(-1, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            value
+                .try_cmp_query(&I32::query(1_000_000, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            value
+                .try_cmp_query(&I32::query(i32::MAX, &field()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn values_compare_across_contexts() {
+        let small = I16::new_with_unsafe_parts(-10, b"row 1", &field()).unwrap();
+        let large = I16::new(1000, b"row 2", &field()).unwrap();
+
+        assert!(small < large);
+    }
+
+    #[test]
+    fn unqueryable_values_cannot_be_queried() {
+        let mut value = U8::new(42, b"row 1", &field()).unwrap();
+        value.make_unqueryable().unwrap();
+
+        assert!(matches!(
+            value.try_cmp_query(&U8::query(42, &field()).unwrap()),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert_eq!(42, value.decrypt(b"row 1", &field()).unwrap());
+    }
+}
@@ -0,0 +1,262 @@
+//! The first version of the fixed-width integer datatypes
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+use super::Integer;
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+///
+/// `T` is the plaintext integer type, and `N` is how many bytes wide it is, which is also how
+/// many blocks there are in the ORE ciphertext.
+///
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+#[doc(hidden)]
+pub struct V1<T, const N: usize> {
+    /// The integer in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the integer
+    #[serde(rename = "o")]
+    ore_ciphertext: Option<OREv1<N, 256>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+    /// What sort of integer is in here
+    #[serde(skip)]
+    plaintext: PhantomData<T>,
+}
+
+#[allow(private_bounds)] // Only our own integer types are ever put in here
+impl<T, const N: usize> V1<T, N>
+where
+    T: Integer,
+    cretrit::PlainText<N, 256>: TryFrom<T::Orderable>,
+    <cretrit::PlainText<N, 256> as TryFrom<T::Orderable>>::Error: std::fmt::Display,
+{
+    /// Encrypt the integer
+    pub(crate) fn new(i: T, context: &[u8], field: &Field) -> Result<Self, Error> {
+        Self::encrypt(i, context, field, false)
+    }
+
+    /// Encrypt the integer in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        i: T,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<Self, Error> {
+        Self::encrypt(i, context, field, true)
+    }
+
+    /// Encrypt the integer for decryption, and its order-preserving form for querying
+    ///
+    fn encrypt(i: T, context: &[u8], field: &Field, include_left: bool) -> Result<Self, Error> {
+        let v = cbor!(i).map_err(|e| {
+            Error::EncodingError(format!("failed to convert {i} to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode {i}: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(Self::ore(i, field, include_left)?),
+            kid: field.key_id()?.into(),
+            plaintext: PhantomData,
+        })
+    }
+
+    /// Encrypt an integer into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore(i: T, field: &Field, include_left: bool) -> Result<OREv1<N, 256>, Error> {
+        if include_left {
+            OREv1::<N, 256>::new_with_left(i.orderable(), T::ORE_KEY_IDENTIFIER, field)
+        } else {
+            OREv1::<N, 256>::new(i.orderable(), T::ORE_KEY_IDENTIFIER, field)
+        }
+    }
+
+    /// Turn the ciphertext back into an integer
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<T, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        ciborium::de::from_reader::<T, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))
+    }
+}
+
+impl<T, const N: usize> V1<T, N> {
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Return the ORE ciphertext, if the integer hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<N, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.ore_ciphertext = None;
+    }
+
+    /// Compare two integers, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
+}
+
+impl<T, const N: usize> Ord for V1<T, N> {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl<T, const N: usize> PartialOrd for V1<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, const N: usize> PartialEq for V1<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, const N: usize> Eq for V1<T, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn assert_sorted<T, const N: usize>(values: &[T])
+    where
+        T: Integer + std::fmt::Debug,
+        cretrit::PlainText<N, 256>: TryFrom<T::Orderable>,
+        <cretrit::PlainText<N, 256> as TryFrom<T::Orderable>>::Error: std::fmt::Display,
+    {
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::<T, N>::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::<T, N>::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l:?} vs {r:?}");
+            }
+        }
+    }
+
+    fn serialised_size<T, const N: usize>(value: &V1<T, N>) -> usize {
+        let mut s: Vec<u8> = vec![];
+        ciborium::ser::into_writer(&cbor!(value).unwrap(), &mut s).unwrap();
+        s.len()
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let value = V1::<i32, 4>::new(i32::MIN, b"context", &field()).unwrap();
+        assert_eq!(i32::MIN, value.decrypt(b"context", &field()).unwrap());
+
+        let value = V1::<u64, 8>::new(u64::MAX, b"context", &field()).unwrap();
+        assert_eq!(u64::MAX, value.decrypt(b"context", &field()).unwrap());
+
+        let value = V1::<i128, 16>::new(i128::MIN, b"context", &field()).unwrap();
+        assert_eq!(i128::MIN, value.decrypt(b"context", &field()).unwrap());
+
+        let value = V1::<u128, 16>::new(u128::MAX, b"context", &field()).unwrap();
+        assert_eq!(u128::MAX, value.decrypt(b"context", &field()).unwrap());
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::<u8, 1>::new(42, b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::<i16, 2>::new(42, b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn integers_compare_correctly() {
+        assert_sorted::<i128, 16>(&[i128::MIN, i128::from(i64::MIN), -1, 0, i128::MAX]);
+        assert_sorted::<u128, 16>(&[0, u128::from(u64::MAX), u128::from(u64::MAX) + 1, u128::MAX]);
+        assert_sorted::<u64, 8>(&[0, 1, 255, 256, u64::MAX]);
+        assert_sorted::<i32, 4>(&[i32::MIN, -256, -1, 0, 1, i32::MAX]);
+        assert_sorted::<i16, 2>(&[i16::MIN, -1, 0, 255, 256, i16::MAX]);
+        assert_sorted::<u8, 1>(&[0, 1, 127, 128, u8::MAX]);
+    }
+
+    #[test]
+    fn narrower_integers_have_smaller_ciphertexts() {
+        let wide = V1::<u64, 8>::new_with_unsafe_parts(42, b"ctx", &field()).unwrap();
+        let middling = V1::<i32, 4>::new_with_unsafe_parts(42, b"ctx", &field()).unwrap();
+        let narrow = V1::<u8, 1>::new_with_unsafe_parts(42, b"ctx", &field()).unwrap();
+
+        assert!(serialised_size(&narrow) < serialised_size(&middling));
+        assert!(serialised_size(&middling) < serialised_size(&wide));
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let value = V1::<i32, 4>::new_with_unsafe_parts(42, b"ctx", &field()).unwrap();
+        let safe = V1::<i32, 4>::new(42, b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::<i32, 4>::new(42, b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
@@ -13,7 +13,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::field::KeyId;
+use crate::{field::KeyId, Error};
 
 /// A collection of encrypted values that all represent the same plaintext
 ///
@@ -71,6 +71,27 @@ where
             .find(|m| m.is_compatible(other))
             .cloned()
     }
+
+    /// Compare the queryable part of a stored value against the compatible member of the `Kith`
+    ///
+    /// This is the plumbing behind the `try_cmp_query()` (and friends) of the datatypes: `value`
+    /// is whatever the stored value has to be compared with, which is `None` if the value has been
+    /// made unqueryable, and `compare` gets the value and the matching member, in that order.
+    ///
+    pub(crate) fn compare_with<T>(
+        &self,
+        value: Option<CT>,
+        compare: impl FnOnce(&CT, &CT) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let value = value.ok_or_else(|| {
+            Error::MissingComponentError("value has no queryable ciphertext".to_string())
+        })?;
+        let member = self
+            .compatible_member(&value)
+            .ok_or_else(Error::KeyMismatchError)?;
+
+        compare(&value, &member)
+    }
 }
 
 impl<CT> Default for Kith<CT>
@@ -105,3 +126,56 @@ pub trait Datatype {
     /// Return a numeric indicator of the version of this data type
     fn ciphertext_version(&self) -> u32;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{datatype::ORE, key_provider::Static, Field, Root};
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+
+    fn field(name: &[u8]) -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", name)
+        .unwrap()
+    }
+
+    fn query(i: u64, field: &Field) -> Kith<ORE<8, 256>> {
+        let mut k = Kith::new();
+        k.add_member(ORE::new_with_unsafe_parts(i, b"test", field).unwrap());
+        k
+    }
+
+    #[test]
+    fn values_are_compared_against_the_compatible_member() {
+        let value = ORE::<8, 256>::new(42u64, b"test", &field(b"bar")).unwrap();
+        let mut q = query(41, &field(b"baz"));
+        q.add_member(ORE::new_with_unsafe_parts(41u64, b"test", &field(b"bar")).unwrap());
+
+        assert_eq!(
+            Ordering::Greater,
+            q.compare_with(Some(value), ORE::try_cmp).unwrap()
+        );
+    }
+
+    #[test]
+    fn unqueryable_values_cannot_be_compared() {
+        assert!(matches!(
+            query(42, &field(b"bar")).compare_with(None, ORE::try_cmp),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+
+    #[test]
+    fn values_from_other_fields_cannot_be_compared() {
+        let value = ORE::<8, 256>::new(42u64, b"test", &field(b"bar")).unwrap();
+
+        assert!(matches!(
+            query(42, &field(b"baz")).compare_with(Some(value), ORE::try_cmp),
+            Err(Error::KeyMismatchError())
+        ));
+    }
+}
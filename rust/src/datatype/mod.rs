@@ -6,13 +6,32 @@
 
 mod boolean;
 mod date;
+mod decimal;
+mod duration;
 mod ere;
+mod f64;
 mod i64;
+mod integer;
 mod kith;
 mod ore;
 mod text;
+mod time_of_day;
+mod timestamp;
+mod uuid;
 
-pub use self::{boolean::Boolean, date::Date, i64::I64, text::Text};
+pub use self::{
+    boolean::Boolean,
+    date::{CalendarDate, Date, DateCode},
+    decimal::{Decimal, DecimalCode, DecimalSpec, FixedDecimal},
+    duration::{Duration, SignedDuration},
+    f64::F64,
+    i64::I64,
+    integer::{I128, I16, I32, U128, U64, U8},
+    text::{EqualityCode, Normalisation, OrderCode, Text, TextOptions},
+    time_of_day::{ClockTime, TimeCode, TimeOfDay},
+    timestamp::{Timestamp, UnixTimestamp},
+    uuid::{Uuid, UuidBytes},
+};
 
 #[doc(hidden)]
 pub use self::{ere::ERE, kith::Kith, ore::ORE};
@@ -106,6 +106,41 @@ impl<const N: usize, const W: u16> ORE<N, W> {
             kid: key_id.into(),
         }
     }
+
+    /// Compare this ORE value with another, returning an error if the comparison can't be done
+    ///
+    /// The `Ord` implementation for `ORE` panics if the values cannot be compared, which is rather
+    /// unfriendly if the values came from somewhere you don't entirely trust (like, say, a
+    /// database).  This method tells you what went wrong instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the values were encrypted with different field keys, if neither value
+    /// contains a left ciphertext, or if either value is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        match (&self.ore_ciphertext, &other.ore_ciphertext) {
+            (Ciphertext::v1(s), Ciphertext::v1(o)) => s.try_cmp(o),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
+
+    /// Determine whether this ORE value is equal to another, returning an error if the comparison
+    /// can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`ORE::try_cmp()`](ORE::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
 }
 
 impl<const N: usize, const W: u16> KithMember for ORE<N, W> {}
@@ -128,17 +163,7 @@ impl<const N: usize, const W: u16> KithDatatype for ORE<N, W> {
 impl<const N: usize, const W: u16> Ord for ORE<N, W> {
     #[allow(clippy::panic)] // No way to signal an error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        match &self.ore_ciphertext {
-            #[allow(clippy::match_wildcard_for_single_variants)]
-            // Yes, I really *do* want to match *anything else*
-            Ciphertext::v1(s) => match &other.ore_ciphertext {
-                Ciphertext::v1(o) => s.cmp(o),
-                _ => panic!("Cannot compare a v1 ORE ciphertext with any other type of ciphertext"),
-            },
-            Ciphertext::Unknown => {
-                panic!("Cannot compare against an Unknown version ORE ciphertext")
-            }
-        }
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -206,6 +231,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn try_cmp_reports_missing_left_ciphertext() {
+        let ca = ORE::<8, 16>::new(8u8, b"test", &field()).unwrap();
+        let cb = ORE::<8, 16>::new(16u8, b"test", &field()).unwrap();
+
+        assert!(matches!(
+            ca.try_cmp(&cb),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+    }
+
+    #[test]
+    fn try_cmp_reports_key_mismatch() {
+        let other_field = Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"baz")
+        .unwrap();
+
+        let ca = ORE::<8, 16>::new_with_unsafe_parts(8u8, b"test", &field()).unwrap();
+        let cb = ORE::<8, 16>::new_with_unsafe_parts(8u8, b"test", &other_field).unwrap();
+
+        assert!(matches!(ca.try_eq(&cb), Err(Error::KeyMismatchError())));
+    }
+
     #[test]
     #[should_panic]
     fn need_one_left_ciphertext() {
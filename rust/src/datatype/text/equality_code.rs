@@ -0,0 +1,157 @@
+//! Encrypted equality codes, for finding texts equal to a given text
+//!
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use super::Normalisation;
+use crate::{
+    crypto::EREv1,
+    datatype::kith::{Datatype as KithDatatype, Member as KithMember},
+    field::KeyId,
+    Error,
+};
+
+/// Versioning support
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[allow(clippy::missing_docs_in_private_items)] // I think we can all tell what's going on in here
+enum Ciphertext {
+    #[allow(non_camel_case_types)]
+    v1(Box<EREv1<16, 16>>),
+    #[allow(non_camel_case_types)]
+    v2(Box<EREv1<32, 16>>),
+    Unknown,
+}
+
+/// The encrypted equality code of a text
+///
+/// This is what you get from [`Text::equality()`](crate::datatype::Text::equality), and what
+/// [`Text::query_equals()`](crate::datatype::Text::query_equals) produces to compare against.
+///
+/// Different versions of `Text` calculate their equality codes differently -- version 1 uses a
+/// 64-bit hash, while version 2 uses a 128-bit one -- and codes of different versions can't be
+/// compared against each other.  The queries produced by `Text::query_equals()` contain a code of
+/// every version, so that a dataset which contains a mixture of versions (for instance, while
+/// part-way through re-encrypting old values) can be queried in one go.
+///
+/// An equality code also knows which normalisation form was applied to the text before it was
+/// hashed, so that a query for a text normalised one way can't silently fail to find texts that
+/// were normalised another.
+///
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EqualityCode {
+    /// The ERE ciphertext of the equality hash
+    #[serde(rename = "e")]
+    ere_ciphertext: Ciphertext,
+
+    /// The normalisation form applied to the text before it was hashed, if it isn't the default
+    /// one
+    #[serde(rename = "n")]
+    normalisation: Option<Normalisation>,
+
+    /// The field key ID which was used to generate this ciphertext
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+impl EqualityCode {
+    /// Create an equality code from a version 1 equality ciphertext
+    ///
+    pub(crate) fn from_ere_v1(
+        e: EREv1<16, 16>,
+        normalisation: Normalisation,
+        key_id: KeyId,
+    ) -> Self {
+        Self {
+            ere_ciphertext: Ciphertext::v1(Box::new(e)),
+            normalisation: Some(normalisation).filter(|n| *n != Normalisation::default()),
+            kid: key_id.into(),
+        }
+    }
+
+    /// Create an equality code from a version 2 equality ciphertext
+    ///
+    pub(crate) fn from_ere_v2(
+        e: EREv1<32, 16>,
+        normalisation: Normalisation,
+        key_id: KeyId,
+    ) -> Self {
+        Self {
+            ere_ciphertext: Ciphertext::v2(Box::new(e)),
+            normalisation: Some(normalisation).filter(|n| *n != Normalisation::default()),
+            kid: key_id.into(),
+        }
+    }
+
+    /// The normalisation form that was applied to the text before the equality code was generated
+    ///
+    #[must_use]
+    pub fn normalisation(&self) -> Normalisation {
+        self.normalisation.unwrap_or_default()
+    }
+
+    /// Determine whether this equality code is equal to another
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the equality codes were encrypted with different field keys, or
+    /// generated with different normalisation forms, if they are of different versions, if
+    /// neither equality code contains a left ciphertext, or if either equality code is of an
+    /// unknown version.
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lnorm = self.normalisation();
+        let rnorm = other.normalisation();
+        if lnorm != rnorm {
+            return Err(Error::NormalisationMismatchError(format!(
+                "{} vs {}",
+                lnorm.name(),
+                rnorm.name()
+            )));
+        }
+
+        match (&self.ere_ciphertext, &other.ere_ciphertext) {
+            (Ciphertext::v1(lhs), Ciphertext::v1(rhs)) => lhs.try_eq(rhs),
+            (Ciphertext::v2(lhs), Ciphertext::v2(rhs)) => lhs.try_eq(rhs),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+            (lhs, rhs) => Err(Error::VersionMismatchError(format!(
+                "v{} vs v{}",
+                lhs.version(),
+                rhs.version()
+            ))),
+        }
+    }
+}
+
+impl Ciphertext {
+    /// The version number of the ciphertext
+    ///
+    fn version(&self) -> u32 {
+        match self {
+            Ciphertext::v1(_) => 1,
+            Ciphertext::v2(_) => 2,
+            Ciphertext::Unknown => 0,
+        }
+    }
+}
+
+impl KithMember for EqualityCode {}
+
+impl KithDatatype for EqualityCode {
+    fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        self.ere_ciphertext.version()
+    }
+}
@@ -1,26 +1,41 @@
 //! Store and query UTF-8 encoded text in an encrypted form
 //!
 
+mod equality_code;
+mod normalisation;
+mod options;
+mod order_code;
+mod phonetic;
 mod v1;
+mod v2;
 
 use serde::{Deserialize, Serialize};
-use std::hash::Hash;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 
-use self::v1::V1;
+pub use self::{
+    equality_code::EqualityCode, normalisation::Normalisation, options::TextOptions,
+    order_code::OrderCode,
+};
+
+use self::{v1::V1, v2::V2};
 use crate::{
+    collator::{Collation, Collator},
     datatype::kith::{Datatype as KithDatatype, Kith},
-    datatype::ORE,
+    datatype::{ERE, ORE},
     field::KeyId,
     Error, Field,
 };
 
 /// Generic UTF-8 text
-#[derive(Debug, Hash, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
 #[non_exhaustive]
 pub enum Text {
     #[allow(non_camel_case_types)]
     v1(Box<V1>),
+    #[allow(non_camel_case_types)]
+    v2(Box<V2>),
     Unknown,
 }
 
@@ -34,7 +49,7 @@ impl Text {
     /// Can return an error if the process of encrypting the data fails.
     ///
     pub fn new(text: &str, context: &[u8], field: &Field) -> Result<Text, Error> {
-        Ok(Text::v1(Box::new(V1::new(text, context, field)?)))
+        Self::new_with_options(text, context, field, &TextOptions::new())
     }
 
     /// Create a new encrypted, queryable UTF-8 text with degraded security
@@ -56,11 +71,433 @@ impl Text {
         field: &Field,
         ordering: Option<u8>,
     ) -> Result<Text, Error> {
-        Ok(Text::v1(Box::new(V1::new_with_unsafe_parts(
-            text, context, field, ordering,
+        let mut options = TextOptions::new().with_unsafe_parts();
+        options.ordering = ordering;
+
+        Self::new_with_options(text, context, field, &options)
+    }
+
+    /// Create a new encrypted, queryable UTF-8 text with optional extra components
+    ///
+    /// The [`TextOptions`] determine which optional components (such as prefix search tokens or
+    /// an ordering code) are included in the ciphertext, and whether the ciphertext is permitted
+    /// to contain components that allow an attacker to infer the plaintext or some part(s)
+    /// thereof.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the process of encrypting the data fails, or if the options ask for
+    /// a component that can only be included with unsafe parts, without also asking for unsafe
+    /// parts.
+    ///
+    pub fn new_with_options(
+        text: &str,
+        context: &[u8],
+        field: &Field,
+        options: &TextOptions,
+    ) -> Result<Text, Error> {
+        Ok(Text::v2(Box::new(V2::new_with_options(
+            text, context, field, options,
         )?)))
     }
 
+    /// The parts of the text which are the same in every version
+    ///
+    fn base(&self) -> Option<&V1> {
+        match self {
+            Text::v1(t) => Some(t),
+            Text::v2(t) => Some(t.base()),
+            Text::Unknown => None,
+        }
+    }
+
+    /// Get the encrypted equality ciphertext of the text
+    ///
+    /// This is the value that is used to determine whether two texts are equal.  It is returned
+    /// as an [`EqualityCode`], so that it can be compared against the values produced by
+    /// [`Text::query_equals()`](Text::query_equals), without needing to construct a whole `Text`
+    /// to query with.
+    ///
+    /// If the text has been made unqueryable, this method will return `None`.
+    ///
+    #[must_use]
+    pub fn equality(&self) -> Option<EqualityCode> {
+        match self {
+            Text::v1(t) => t
+                .equality()
+                .map(|e| EqualityCode::from_ere_v1(e, t.normalisation(), t.key_id())),
+            Text::v2(t) => t
+                .equality()
+                .map(|e| EqualityCode::from_ere_v2(e, t.base().normalisation(), t.base().key_id())),
+            Text::Unknown => None,
+        }
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts equal to the given text
+    ///
+    /// The text is normalised in the same way as when a `Text` is created, and then encrypted
+    /// with the field's equality key, so the result can be compared against the value returned
+    /// by [`Text::equality()`](Text::equality) for any text encrypted using the same field.
+    ///
+    /// The query contains an equality code for every version of `Text`, so it will find matching
+    /// texts whether or not they have been [upgraded](Text::upgrade) yet.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query_equals(text: &str, field: &Field) -> Result<Kith<EqualityCode>, Error> {
+        Self::query_equals_with_options(text, field, &TextOptions::new())
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts equal to the given text,
+    /// where the texts were created with non-default options
+    ///
+    /// As per [`Text::query_equals()`](Text::query_equals), except that the text is normalised
+    /// according to the `options`, which should be the same ones that were used to create the
+    /// texts being queried.  Comparing the query against a text that was normalised differently
+    /// is an error, rather than a non-match.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query_equals_with_options(
+        text: &str,
+        field: &Field,
+        options: &TextOptions,
+    ) -> Result<Kith<EqualityCode>, Error> {
+        let key_id = field.key_id()?;
+
+        let mut k = Kith::new();
+        k.add_member(EqualityCode::from_ere_v1(
+            V1::ere_equality(text, options.normalisation, field, true)?,
+            options.normalisation,
+            key_id,
+        ));
+        k.add_member(EqualityCode::from_ere_v2(
+            V2::ere_equality(text, options.normalisation, field, true)?,
+            options.normalisation,
+            key_id,
+        ));
+        Ok(k)
+    }
+
+    /// Get the encrypted case- and accent-insensitive equality ciphertext of the text
+    ///
+    /// This is only present if the text was created with
+    /// [`TextOptions::with_folded_equality()`], and can be compared against the values produced by
+    /// [`Text::query_equals_folded()`](Text::query_equals_folded).
+    ///
+    /// If the text has been made unqueryable, or was created without a folded equality
+    /// ciphertext, this method will return `None`.
+    ///
+    #[must_use]
+    pub fn folded_equality(&self) -> Option<ERE<16, 16>> {
+        self.base()
+            .and_then(|t| t.folded_equality().map(|e| ERE::from_ere_v1(e, t.key_id())))
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts equal to the given text,
+    /// disregarding differences in case and accents
+    ///
+    /// The text is folded in the same way as described in
+    /// [`TextOptions::with_folded_equality()`], and the result can be compared against the value
+    /// returned by [`Text::folded_equality()`](Text::folded_equality).
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the encryption process fails.
+    ///
+    pub fn query_equals_folded(text: &str, field: &Field) -> Result<Kith<ERE<16, 16>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ERE::from_ere_v1(
+            V1::ere_folded_equality(text, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Get the encrypted phonetic equality ciphertext of the text
+    ///
+    /// This is only present if the text was created with [`TextOptions::with_phonetic()`], and
+    /// can be compared against the values produced by
+    /// [`Text::query_sounds_like()`](Text::query_sounds_like).
+    ///
+    /// If the text has been made unqueryable, or was created without a phonetic equality
+    /// ciphertext, this method will return `None`.
+    ///
+    #[must_use]
+    pub fn phonetic(&self) -> Option<ERE<16, 16>> {
+        self.base()
+            .and_then(|t| t.phonetic().map(|e| ERE::from_ere_v1(e, t.key_id())))
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts that sound like the given
+    /// name
+    ///
+    /// The name is reduced to its phonetic key in the same way as described in
+    /// [`TextOptions::with_phonetic()`], and the result can be compared against the value
+    /// returned by [`Text::phonetic()`](Text::phonetic).
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the name doesn't contain anything that can be phonetically encoded
+    /// (such as a name written entirely in a non-Latin script), or if the encryption process
+    /// fails.
+    ///
+    pub fn query_sounds_like(name: &str, field: &Field) -> Result<Kith<ERE<16, 16>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ERE::from_ere_v1(
+            V1::ere_query_phonetic(name, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Determine whether the text starts with the prefix represented by a prefix query
+    ///
+    /// The `prefix` is a set of encrypted values produced by
+    /// [`Text::query_prefix()`](Text::query_prefix), using the same field as this text.  Only texts
+    /// that were created with prefix tokens (see [`TextOptions::with_prefixes()`]) can be searched
+    /// in this way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text does not have any prefix tokens, if the query does not
+    /// contain a value which can be compared against this text, or if the text is of an unknown
+    /// version.
+    ///
+    pub fn has_prefix(&self, prefix: &Kith<ERE<16, 16>>) -> Result<bool, Error> {
+        let t = self.base().ok_or_else(Error::UnknownVersionError)?;
+
+        let tokens = t
+            .prefixes()
+            .ok_or_else(|| Error::MissingComponentError("text has no prefix tokens".to_string()))?;
+
+        let mut found = false;
+
+        for token in tokens {
+            let token = ERE::from_ere_v1(token, t.key_id());
+            found |= prefix.compare_with(Some(token), ERE::try_eq)?;
+        }
+
+        Ok(found)
+    }
+
+    /// Generate a set of encrypted values suitable for searching for texts with a given prefix
+    ///
+    /// The prefix is normalised in the same way as the texts being searched, and the result is
+    /// suitable for passing to [`Text::has_prefix()`](Text::has_prefix).  Bear in mind that texts
+    /// only store tokens for as many leading characters as were requested when they were
+    /// encrypted, so a prefix query longer than that will never match.  If you need to search for
+    /// longer prefixes, query for the longest prefix that is stored, and filter the decrypted
+    /// results.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the prefix is empty, is longer than the longest possible stored
+    /// prefix, or if the encryption process fails.
+    ///
+    pub fn query_prefix(prefix: &str, field: &Field) -> Result<Kith<ERE<16, 16>>, Error> {
+        Self::query_prefix_with_options(prefix, field, &TextOptions::new())
+    }
+
+    /// Generate a set of encrypted values suitable for searching for texts with a given prefix,
+    /// where the texts were created with non-default options
+    ///
+    /// As per [`Text::query_prefix()`](Text::query_prefix), except that the prefix is normalised
+    /// according to the `options`, which should be the same ones that were used to create the
+    /// texts being searched.  Prefix tokens don't record the normalisation form they were
+    /// generated with, so if the normalisation forms differ, the query won't match, rather than
+    /// returning an error.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the prefix is empty, is longer than the longest possible stored
+    /// prefix, or if the encryption process fails.
+    ///
+    pub fn query_prefix_with_options(
+        prefix: &str,
+        field: &Field,
+        options: &TextOptions,
+    ) -> Result<Kith<ERE<16, 16>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ERE::from_ere_v1(
+            V1::ere_query_prefix(prefix, options.normalisation, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Determine whether the text might contain the substring represented by a substring query
+    ///
+    /// The `needle` is the set of encrypted trigrams produced by
+    /// [`Text::query_contains()`](Text::query_contains), using the same field as this text.  Only
+    /// texts that were created with substring tokens (see [`TextOptions::with_substrings()`]) can
+    /// be searched in this way.
+    ///
+    /// A return value of `true` means that the text contains every trigram of the needle, which
+    /// is *not* a guarantee that it contains the needle itself; you'll need to check the
+    /// decrypted text to be sure.  A return value of `false`, on the other hand, means that the
+    /// text definitely does not contain the needle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text does not have any substring tokens, if the query does not
+    /// contain values which can be compared against this text, or if the text is of an unknown
+    /// version.
+    ///
+    pub fn may_contain(&self, needle: &[Kith<ERE<16, 16>>]) -> Result<bool, Error> {
+        let t = self.base().ok_or_else(Error::UnknownVersionError)?;
+
+        let tokens = t
+            .trigrams()
+            .ok_or_else(|| {
+                Error::MissingComponentError("text has no substring tokens".to_string())
+            })?
+            .into_iter()
+            .map(|tg| ERE::from_ere_v1(tg, t.key_id()))
+            .collect::<Vec<_>>();
+
+        for trigram in needle {
+            let mut found = false;
+
+            for token in &tokens {
+                found |= trigram.compare_with(Some(token.clone()), ERE::try_eq)?;
+            }
+
+            if !found {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Generate the set of encrypted values needed to search for texts containing a substring
+    ///
+    /// The needle is normalised in the same way as the texts being searched, broken into its
+    /// trigrams, and each trigram is encrypted with the field's substring key.  The result is
+    /// suitable for passing to [`Text::may_contain()`](Text::may_contain).
+    ///
+    /// See [`TextOptions::with_substrings()`] for the details of what substring searching gives
+    /// away about your data.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the needle is shorter than three characters (and so has no
+    /// trigrams), or if the encryption process fails.
+    ///
+    pub fn query_contains(needle: &str, field: &Field) -> Result<Vec<Kith<ERE<16, 16>>>, Error> {
+        Self::query_contains_with_options(needle, field, &TextOptions::new())
+    }
+
+    /// Generate the set of encrypted values needed to search for texts containing a substring,
+    /// where the texts were created with non-default options
+    ///
+    /// As per [`Text::query_contains()`](Text::query_contains), except that the needle is
+    /// normalised according to the `options`, which should be the same ones that were used to
+    /// create the texts being searched.  Substring tokens don't record the normalisation form they
+    /// were generated with, so if the normalisation forms differ, the query won't match, rather
+    /// than returning an error.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the needle is shorter than three characters (and so has no
+    /// trigrams), or if the encryption process fails.
+    ///
+    pub fn query_contains_with_options(
+        needle: &str,
+        field: &Field,
+        options: &TextOptions,
+    ) -> Result<Vec<Kith<ERE<16, 16>>>, Error> {
+        let key_id = field.key_id()?;
+
+        Ok(
+            V1::ere_query_trigrams(needle, options.normalisation, field)?
+                .into_iter()
+                .map(|tg| {
+                    let mut k = Kith::new();
+                    k.add_member(ERE::from_ere_v1(tg, key_id));
+                    k
+                })
+                .collect(),
+        )
+    }
+
+    /// Determine whether the text contains the word represented by a word query
+    ///
+    /// The `word` is a set of encrypted values produced by
+    /// [`Text::query_word()`](Text::query_word), using the same field as this text.  Only texts
+    /// that were created with word tokens (see [`TextOptions::with_words()`]) can be searched in
+    /// this way.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text does not have any word tokens, if the query does not contain
+    /// a value which can be compared against this text, or if the text is of an unknown version.
+    ///
+    pub fn has_word(&self, word: &Kith<ERE<16, 16>>) -> Result<bool, Error> {
+        let t = self.base().ok_or_else(Error::UnknownVersionError)?;
+
+        let tokens = t
+            .words()
+            .ok_or_else(|| Error::MissingComponentError("text has no word tokens".to_string()))?;
+
+        let mut found = false;
+
+        for token in tokens {
+            let token = ERE::from_ere_v1(token, t.key_id());
+            found |= word.compare_with(Some(token), ERE::try_eq)?;
+        }
+
+        Ok(found)
+    }
+
+    /// Generate a set of encrypted values suitable for searching for texts containing a word
+    ///
+    /// The word is normalised and lowercased in the same way as the words of the texts being
+    /// searched, and the result is suitable for passing to [`Text::has_word()`](Text::has_word).
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if `word` doesn't consist of exactly one word (so no whitespace, and
+    /// no punctuation that separates words), or if the encryption process fails.
+    ///
+    pub fn query_word(word: &str, field: &Field) -> Result<Kith<ERE<16, 16>>, Error> {
+        Self::query_word_with_options(word, field, &TextOptions::new())
+    }
+
+    /// Generate a set of encrypted values suitable for searching for texts containing a word,
+    /// where the texts were created with non-default options
+    ///
+    /// As per [`Text::query_word()`](Text::query_word), except that the word is normalised
+    /// according to the `options`, which should be the same ones that were used to create the
+    /// texts being searched.  Word tokens don't record the normalisation form they were generated
+    /// with, so if the normalisation forms differ, the query won't match, rather than returning
+    /// an error.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if `word` doesn't consist of exactly one word, or if the encryption
+    /// process fails.
+    ///
+    pub fn query_word_with_options(
+        word: &str,
+        field: &Field,
+        options: &TextOptions,
+    ) -> Result<Kith<ERE<16, 16>>, Error> {
+        let mut k = Kith::new();
+        k.add_member(ERE::from_ere_v1(
+            V1::ere_query_word(word, options.normalisation, field)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
     /// Get the encrypted length of the text
     ///
     /// The "length" of a text is defined as the number of Unicode Scalar Values present in the
@@ -75,10 +512,8 @@ impl Text {
     ///
     #[must_use]
     pub fn length(&self) -> Option<ORE<8, 16>> {
-        match self {
-            Text::v1(t) => t.length().map(|l| ORE::from_ore_v1(l, t.key_id())),
-            Text::Unknown => None,
-        }
+        self.base()
+            .and_then(|t| t.length().map(|l| ORE::from_ore_v1(l, t.key_id())))
     }
 
     /// Generate a set of encrypted values suitable for comparing against text lengths
@@ -103,6 +538,188 @@ impl Text {
         Ok(k)
     }
 
+    /// Get the encrypted ordering code of the text
+    ///
+    /// This is only present if the text was created with an ordering code (see
+    /// [`TextOptions::with_ordering()`]), and can be compared against the values produced by
+    /// [`Text::query_order()`](Text::query_order).
+    ///
+    /// If the text has no ordering code, this method will return `None`.
+    ///
+    #[must_use]
+    pub fn order_code(&self) -> Option<OrderCode> {
+        self.base().and_then(|t| {
+            t.ordering().map(|o| {
+                OrderCode::from_ore_v1(o, t.collation_identifier(), t.normalisation(), t.key_id())
+            })
+        })
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts which sort before or after
+    /// the given text
+    ///
+    /// This is what you need for range queries, like `WHERE name >= 'M'`.  The `prefix_len` is
+    /// the number of elements of the text's sort key that are encrypted, and should be the same
+    /// as the length of the ordering codes of the texts being queried (see
+    /// [`TextOptions::with_ordering()`]); only as many elements as both the query and the stored
+    /// text have are compared.  The text is sorted according to the default collation; if the
+    /// texts being queried were created with a different one, use
+    /// [`Text::query_order_with_collator()`](Text::query_order_with_collator) instead, or
+    /// [`Text::query_order_with_options()`](Text::query_order_with_options) if they were created
+    /// with any other non-default options.
+    ///
+    /// The result is suitable for passing to [`Text::try_cmp_order()`](Text::try_cmp_order).
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if `prefix_len` is zero, if the text cannot be collated, or if the
+    /// encryption process fails.
+    ///
+    pub fn query_order(
+        text: &str,
+        field: &Field,
+        prefix_len: u8,
+    ) -> Result<Kith<OrderCode>, Error> {
+        Self::query_order_with_collator(text, field, prefix_len, &Collation::default())
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts which sort before or after
+    /// the given text, according to a particular collator
+    ///
+    /// As per [`Text::query_order()`](Text::query_order), except that the text is sorted by the
+    /// given `collator`, which must be the same one that was used to create the texts being
+    /// queried.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if `prefix_len` is zero, if the text cannot be collated, or if the
+    /// encryption process fails.
+    ///
+    pub fn query_order_with_collator(
+        text: &str,
+        field: &Field,
+        prefix_len: u8,
+        collator: &dyn Collator,
+    ) -> Result<Kith<OrderCode>, Error> {
+        Self::order_query(text, field, prefix_len, collator, Normalisation::default())
+    }
+
+    /// Generate a set of encrypted values suitable for finding texts which sort before or after
+    /// the given text, where the texts were created with non-default options
+    ///
+    /// As per [`Text::query_order()`](Text::query_order), except that the text is normalised and
+    /// collated according to the `options`, which should be the same ones that were used to
+    /// create the texts being queried.  The query records the collation and normalisation form it
+    /// was generated with, so comparing it against a text created with different ones is an
+    /// error, rather than a quietly wrong answer.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if `prefix_len` is zero, if the text cannot be collated, or if the
+    /// encryption process fails.
+    ///
+    pub fn query_order_with_options(
+        text: &str,
+        field: &Field,
+        prefix_len: u8,
+        options: &TextOptions,
+    ) -> Result<Kith<OrderCode>, Error> {
+        Self::order_query(
+            text,
+            field,
+            prefix_len,
+            &*options.collator,
+            options.normalisation,
+        )
+    }
+
+    /// Do the actual work of generating an ordering query
+    ///
+    fn order_query(
+        text: &str,
+        field: &Field,
+        prefix_len: u8,
+        collator: &dyn Collator,
+        normalisation: Normalisation,
+    ) -> Result<Kith<OrderCode>, Error> {
+        let mut k = Kith::new();
+        k.add_member(OrderCode::from_ore_v1(
+            V1::ore_query_order(text, prefix_len, collator, normalisation, field)?,
+            &collator.identifier(),
+            normalisation,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare the ordering of this text with the text represented by an ordering query
+    ///
+    /// The `query` is a set of encrypted values produced by
+    /// [`Text::query_order()`](Text::query_order), using the same field as this text.  The result
+    /// says where this text sorts relative to the queried text, so `Ordering::Greater` means that
+    /// this text would satisfy `text > query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text does not have an ordering code, if the query does not contain
+    /// a value which can be compared against this text, if the query was generated with a
+    /// different collation or normalisation form to this text, or if the text is of an unknown
+    /// version.
+    ///
+    pub fn try_cmp_order(&self, query: &Kith<OrderCode>) -> Result<Ordering, Error> {
+        if self.base().is_none() {
+            return Err(Error::UnknownVersionError());
+        }
+
+        query.compare_with(self.order_code(), OrderCode::try_cmp)
+    }
+
+    /// Compare the ordering of this text with another, without panicking if it can't be done
+    ///
+    /// Texts are only orderable if they were created with an ordering code (see
+    /// [`Text::new_with_unsafe_parts()`](Text::new_with_unsafe_parts)).  The `Ord` implementation
+    /// for `Text` panics if the comparison cannot be performed; this method returns an error
+    /// instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texts were encrypted with different field keys, if either text does
+    /// not have an ordering code, if neither text has the left ciphertexts required to perform the
+    /// comparison, or if either text is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        // Ordering codes haven't changed between versions, so any two texts can be compared
+        match (self.base(), other.base()) {
+            (Some(s), Some(o)) => s.try_cmp(o),
+            _ => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this text is equal to another, without panicking if it can't be done
+    ///
+    /// Texts of different versions calculate their equality ciphertexts differently, and so can't
+    /// be compared; [upgrade](Text::upgrade) the older one first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the texts were encrypted with different field keys, if either text has
+    /// been made unqueryable, if neither text has the left ciphertext required to perform the
+    /// comparison, if the texts are of different versions, or if either text is of an unknown
+    /// version.
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        match (self, other) {
+            (Text::v1(s), Text::v1(o)) => s.try_eq(o),
+            (Text::v2(s), Text::v2(o)) => s.try_eq(o),
+            (Text::Unknown, _) | (_, Text::Unknown) => Err(Error::UnknownVersionError()),
+            (s, o) => Err(Error::VersionMismatchError(format!(
+                "v{} vs v{}",
+                s.ciphertext_version(),
+                o.ciphertext_version()
+            ))),
+        }
+    }
+
     /// Decrypt a text
     ///
     /// The `context` parameter must match the value of the `context` parameter passed to
@@ -115,10 +732,9 @@ impl Text {
     /// [`Text::new()`](Text::new) for more details about encryption and decryption contexts.
     ///
     pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<String, Error> {
-        match self {
-            Text::v1(t) => t.decrypt(context, field),
-            Text::Unknown => Err(Error::UnknownVersionError()),
-        }
+        self.base()
+            .ok_or_else(Error::UnknownVersionError)?
+            .decrypt(context, field)
     }
 
     /// Remove the ability to perform any queries on this text
@@ -138,23 +754,375 @@ impl Text {
                 t.make_unqueryable();
                 Ok(())
             }
+            Text::v2(t) => {
+                t.make_unqueryable();
+                Ok(())
+            }
+            Text::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Convert this text to the latest version
+    ///
+    /// Older versions of `Text` remain readable and queryable, but new texts are always created
+    /// with the latest version, and texts of different versions can't be compared for equality
+    /// against each other.  This method converts a text in place, carrying over all of its
+    /// queryable components; only those parts which have changed between versions (such as the
+    /// equality ciphertext, which became a 128-bit hash in version 2) are recalculated, which
+    /// requires decrypting the text.  The text is otherwise not re-encrypted, so the `context`
+    /// must be the same one that was used when the text was created.
+    ///
+    /// Texts which are already the latest version are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the text could not be decrypted, if the encryption process fails,
+    /// or if the text is of an unknown version.
+    ///
+    pub fn upgrade(&mut self, context: &[u8], field: &Field) -> Result<(), Error> {
+        match self {
+            Text::v1(t) => {
+                *self = Text::v2(Box::new(V2::from_v1(t, context, field)?));
+                Ok(())
+            }
+            Text::v2(_) => Ok(()),
             Text::Unknown => Err(Error::UnknownVersionError()),
         }
     }
 }
 
-impl KithDatatype for Text {
-    fn key_id(&self) -> KeyId {
+impl Hash for Text {
+    fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
-            Text::v1(t) => t.key_id(),
-            Text::Unknown => Default::default(),
+            Text::v1(t) => t.hash(state),
+            Text::v2(t) => t.hash(state),
+            Text::Unknown => (),
         }
     }
+}
+
+impl Ord for Text {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Text {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Text {
+    #[allow(clippy::panic)] // No way to signal error from impl PartialEq
+    fn eq(&self, other: &Self) -> bool {
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl Eq for Text {}
+
+impl KithDatatype for Text {
+    fn key_id(&self) -> KeyId {
+        self.base().map(V1::key_id).unwrap_or_default()
+    }
 
     fn ciphertext_version(&self) -> u32 {
         match self {
             Text::v1(_) => 1,
+            Text::v2(_) => 2,
             Text::Unknown => 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collator::Bytes, key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn prefix_search() {
+        let opts = TextOptions::new().with_prefixes(4);
+        let text = Text::new_with_options("Enquo", b"context", &field(), &opts).unwrap();
+
+        assert!(text
+            .has_prefix(&Text::query_prefix("Enq", &field()).unwrap())
+            .unwrap());
+        assert!(!text
+            .has_prefix(&Text::query_prefix("Enx", &field()).unwrap())
+            .unwrap());
+    }
+
+    #[test]
+    fn substring_search() {
+        let opts = TextOptions::new().with_unsafe_parts().with_substrings();
+        let text = Text::new_with_options("Hello, Enquo!", b"context", &field(), &opts).unwrap();
+
+        assert!(text
+            .may_contain(&Text::query_contains("lo, En", &field()).unwrap())
+            .unwrap());
+        assert!(!text
+            .may_contain(&Text::query_contains("Enquiry", &field()).unwrap())
+            .unwrap());
+    }
+
+    #[test]
+    fn range_query() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let text = Text::new_with_options("Enquo", b"context", &field(), &opts).unwrap();
+
+        assert_eq!(
+            Ordering::Greater,
+            text.try_cmp_order(&Text::query_order("A", &field(), 8).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            text.try_cmp_order(&Text::query_order("Enquo", &field(), 8).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            text.try_cmp_order(&Text::query_order("M", &field(), 8).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn range_query_needs_ordering_code() {
+        let text = Text::new("Enquo", b"context", &field()).unwrap();
+
+        assert!(matches!(
+            text.try_cmp_order(&Text::query_order("M", &field(), 8).unwrap()),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+
+    #[test]
+    fn range_query_needs_matching_collation() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_collator(Arc::new(Bytes));
+        let text = Text::new_with_options("Enquo", b"context", &field(), &opts).unwrap();
+
+        assert!(matches!(
+            text.try_cmp_order(&Text::query_order("M", &field(), 8).unwrap()),
+            Err(Error::CollationMismatchError(_))
+        ));
+        assert_eq!(
+            Ordering::Less,
+            text.try_cmp_order(&Text::query_order_with_collator("M", &field(), 8, &Bytes).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn word_search() {
+        let opts = TextOptions::new().with_unsafe_parts().with_words();
+        let text =
+            Text::new_with_options("The quick brown fox.", b"context", &field(), &opts).unwrap();
+
+        assert!(text
+            .has_word(&Text::query_word("Fox", &field()).unwrap())
+            .unwrap());
+        assert!(text
+            .has_word(&Text::query_word("the", &field()).unwrap())
+            .unwrap());
+        assert!(!text
+            .has_word(&Text::query_word("qui", &field()).unwrap())
+            .unwrap());
+    }
+
+    #[test]
+    fn queries_use_the_stored_normalisation() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_words()
+            .with_normalisation(Normalisation::Nfkc);
+        let text =
+            Text::new_with_options("Room \u{ff11}\u{ff10}\u{ff11}", b"context", &field(), &opts)
+                .unwrap();
+
+        assert!(Text::query_equals_with_options("Room 101", &field(), &opts)
+            .unwrap()
+            .compatible_member(&text.equality().unwrap())
+            .unwrap()
+            .try_eq(&text.equality().unwrap())
+            .unwrap());
+        assert!(text
+            .has_word(&Text::query_word_with_options("101", &field(), &opts).unwrap())
+            .unwrap());
+        assert!(!text
+            .has_word(&Text::query_word("\u{ff11}\u{ff10}\u{ff11}", &field()).unwrap())
+            .unwrap());
+        assert!(matches!(
+            Text::query_equals("Room 101", &field())
+                .unwrap()
+                .compatible_member(&text.equality().unwrap())
+                .unwrap()
+                .try_eq(&text.equality().unwrap()),
+            Err(Error::NormalisationMismatchError(_))
+        ));
+        assert_eq!(
+            Ordering::Equal,
+            text.try_cmp_order(
+                &Text::query_order_with_options("Room 101", &field(), 8, &opts).unwrap()
+            )
+            .unwrap()
+        );
+        assert!(matches!(
+            text.try_cmp_order(&Text::query_order("Room 101", &field(), 8).unwrap()),
+            Err(Error::NormalisationMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn prefix_search_needs_prefix_tokens() {
+        let text = Text::new("Enquo", b"context", &field()).unwrap();
+
+        assert!(matches!(
+            text.has_prefix(&Text::query_prefix("Enq", &field()).unwrap()),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+
+    fn v1_text(text: &str, options: &TextOptions) -> Text {
+        Text::v1(Box::new(
+            V1::new_with_options(text, b"context", &field(), options).unwrap(),
+        ))
+    }
+
+    fn query_matches(text: &Text, query: &Kith<EqualityCode>) -> bool {
+        let code = text.equality().unwrap();
+
+        query
+            .compatible_member(&code)
+            .unwrap()
+            .try_eq(&code)
+            .unwrap()
+    }
+
+    #[test]
+    fn new_texts_are_v2() {
+        let text = Text::new("Enquo", b"context", &field()).unwrap();
+
+        assert_eq!(2, text.ciphertext_version());
+        assert_eq!(2, text.equality().unwrap().ciphertext_version());
+    }
+
+    #[test]
+    fn equality_queries_match_every_version() {
+        let v1 = v1_text("Enquo", &TextOptions::new());
+        let v2 = Text::new("Enquo", b"context", &field()).unwrap();
+
+        let query = Text::query_equals("Enquo", &field()).unwrap();
+        assert!(query_matches(&v1, &query));
+        assert!(query_matches(&v2, &query));
+
+        let query = Text::query_equals("Enquiry", &field()).unwrap();
+        assert!(!query_matches(&v1, &query));
+        assert!(!query_matches(&v2, &query));
+    }
+
+    #[test]
+    fn mixed_versions_cannot_be_compared_for_equality() {
+        let v1 = v1_text("Enquo", &TextOptions::new().with_unsafe_parts());
+        let v2 = Text::new("Enquo", b"context", &field()).unwrap();
+
+        assert!(matches!(
+            v1.try_eq(&v2),
+            Err(Error::VersionMismatchError(_))
+        ));
+        assert!(matches!(
+            v1.equality().unwrap().try_eq(&v2.equality().unwrap()),
+            Err(Error::VersionMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn mixed_versions_can_be_ordered() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let v1 = v1_text("Enquo", &opts);
+        let v2 = Text::new_with_options("Ruby", b"context", &field(), &opts).unwrap();
+
+        assert_eq!(Ordering::Less, v1.try_cmp(&v2).unwrap());
+        assert_eq!(Ordering::Greater, v2.try_cmp(&v1).unwrap());
+    }
+
+    #[test]
+    fn upgraded_texts_keep_their_components() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_prefixes(4)
+            .with_normalisation(Normalisation::Nfkc);
+        let mut text = v1_text("Room \u{ff11}\u{ff10}\u{ff11}", &opts);
+        text.upgrade(b"context", &field()).unwrap();
+
+        assert_eq!(2, text.ciphertext_version());
+        assert_eq!(
+            "Room \u{ff11}\u{ff10}\u{ff11}",
+            text.decrypt(b"context", &field()).unwrap()
+        );
+        assert!(text
+            .try_eq(&Text::new_with_options("Room 101", b"context", &field(), &opts).unwrap())
+            .unwrap());
+        assert!(text
+            .has_prefix(&Text::query_prefix_with_options("Room", &field(), &opts).unwrap())
+            .unwrap());
+        assert_eq!(
+            Ordering::Equal,
+            text.try_cmp_order(
+                &Text::query_order_with_options("Room 101", &field(), 8, &opts).unwrap()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn upgrading_keeps_texts_safe() {
+        let mut text = v1_text("Enquo", &TextOptions::new());
+        text.upgrade(b"context", &field()).unwrap();
+
+        // Neither side of the comparison has a left ciphertext
+        assert!(matches!(
+            text.try_eq(&Text::new("Enquo", b"context", &field()).unwrap()),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+    }
+
+    #[test]
+    fn upgrading_unqueryable_texts_keeps_them_unqueryable() {
+        let mut text = v1_text("Enquo", &TextOptions::new());
+        text.make_unqueryable().unwrap();
+        text.upgrade(b"context", &field()).unwrap();
+
+        assert_eq!(2, text.ciphertext_version());
+        assert!(text.equality().is_none());
+    }
+
+    #[test]
+    fn upgrading_needs_the_right_context() {
+        let mut text = v1_text("Enquo", &TextOptions::new());
+
+        assert!(matches!(
+            text.upgrade(b"wrong", &field()),
+            Err(Error::DecryptionError(_))
+        ));
+        assert_eq!(1, text.ciphertext_version());
+    }
+}
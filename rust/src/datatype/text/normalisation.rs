@@ -0,0 +1,70 @@
+//! Choosing how texts are tidied up before they're hashed and sorted
+//!
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// The Unicode normalisation form applied to a text before its queryable parts are generated
+///
+/// Unicode has more than one way to write a lot of things -- "é" can be a single codepoint, or an
+/// "e" followed by a combining acute accent -- and so, for equality and ordering to work the way
+/// people expect, texts are normalised before being hashed or sorted.  The encrypted value itself
+/// is never normalised, so you always get back exactly what you put in.
+///
+/// Which normalisation form is "right" depends on your data.  The default, NFC, only folds
+/// together things that are *canonically* equivalent, which is almost always what you want.  The
+/// compatibility forms (NFKC and NFKD) go further, and treat things like full-width and
+/// half-width digits, or the "ﬁ" ligature and "fi", as the same.  If you'd rather texts be
+/// compared exactly as they are, `Verbatim` does no normalisation at all.
+///
+/// The normalisation form used is recorded in the ciphertext, and texts encrypted with different
+/// normalisation forms cannot be compared against each other.  Queries need to be generated with
+/// the same normalisation form as the texts they're being compared against, using the
+/// `query_*_with_options` functions on [`Text`](crate::datatype::Text).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Normalisation {
+    /// Canonical decomposition, followed by canonical composition
+    #[default]
+    #[serde(rename = "nfc")]
+    Nfc,
+    /// Canonical decomposition
+    #[serde(rename = "nfd")]
+    Nfd,
+    /// Compatibility decomposition, followed by canonical composition
+    #[serde(rename = "nfkc")]
+    Nfkc,
+    /// Compatibility decomposition
+    #[serde(rename = "nfkd")]
+    Nfkd,
+    /// Leave the text exactly as it is
+    #[serde(rename = "none")]
+    Verbatim,
+}
+
+impl Normalisation {
+    /// Put a text into this normalisation form
+    ///
+    pub(crate) fn apply(self, text: &str) -> String {
+        match self {
+            Normalisation::Nfc => text.nfc().collect(),
+            Normalisation::Nfd => text.nfd().collect(),
+            Normalisation::Nfkc => text.nfkc().collect(),
+            Normalisation::Nfkd => text.nfkd().collect(),
+            Normalisation::Verbatim => text.to_string(),
+        }
+    }
+
+    /// The name by which this normalisation form is known in error messages
+    ///
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Normalisation::Nfc => "NFC",
+            Normalisation::Nfd => "NFD",
+            Normalisation::Nfkc => "NFKC",
+            Normalisation::Nfkd => "NFKD",
+            Normalisation::Verbatim => "no normalisation",
+        }
+    }
+}
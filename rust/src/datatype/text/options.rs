@@ -0,0 +1,257 @@
+//! The knobs and dials for deciding what goes into an encrypted text
+//!
+
+use std::sync::Arc;
+
+use super::Normalisation;
+use crate::collator::{Collation, Collator};
+
+/// Control which optional components are included in an encrypted `Text`
+///
+/// By default, a `Text` only contains the components needed to test for equality and compare
+/// lengths, all encrypted in a form that doesn't leak anything until they're queried.  Some sorts
+/// of queries need more than that, and this is where you ask for it.
+///
+/// Options are set by chaining the `with_*` methods:
+///
+/// ```rust
+/// use enquo_core::datatype::TextOptions;
+///
+/// let opts = TextOptions::new().with_unsafe_parts().with_ordering(8).with_prefixes(4);
+/// ```
+///
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // They're independent on/off switches, not a state machine in disguise
+pub struct TextOptions {
+    /// Whether to include the "left" parts of the ciphertexts, and other components that allow
+    /// an attacker to infer something about the plaintext
+    pub(crate) unsafe_parts: bool,
+    /// The normalisation form to apply to the text before generating the queryable components
+    pub(crate) normalisation: Normalisation,
+    /// How many elements of the collation sort key to include in the ordering code, if any
+    pub(crate) ordering: Option<u8>,
+    /// The thing which turns the text into the sort key from which the ordering code is generated
+    pub(crate) collator: Arc<dyn Collator>,
+    /// Whether to include a case- and accent-insensitive equality ciphertext
+    pub(crate) folded_equality: bool,
+    /// Whether to include an equality ciphertext of the text's phonetic key
+    pub(crate) phonetic: bool,
+    /// How many of the leading characters of the text to generate prefix search tokens for, if
+    /// any
+    pub(crate) prefixes: Option<u8>,
+    /// Whether to include the tokens needed for substring searching
+    pub(crate) substrings: bool,
+    /// Whether to include the tokens needed for keyword searching
+    pub(crate) words: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            unsafe_parts: false,
+            normalisation: Normalisation::default(),
+            ordering: None,
+            collator: Arc::new(Collation::default()),
+            folded_equality: false,
+            phonetic: false,
+            prefixes: None,
+            substrings: false,
+            words: false,
+        }
+    }
+}
+
+impl TextOptions {
+    /// Create a new set of options, with nothing optional turned on
+    ///
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include components in the ciphertext that could allow an attacker to infer the plaintext
+    /// or some part(s) thereof
+    ///
+    /// This is the equivalent of encrypting with
+    /// [`Text::new_with_unsafe_parts()`](crate::datatype::Text::new_with_unsafe_parts).  See
+    /// [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    /// Some optional components, such as ordering codes and substring tokens, can *only* be
+    /// stored in a form that leaks information, and so need unsafe parts to be enabled.  The
+    /// documentation for each of those options describes what, exactly, they give away.
+    ///
+    #[must_use]
+    pub fn with_unsafe_parts(mut self) -> Self {
+        self.unsafe_parts = true;
+        self
+    }
+
+    /// Choose the Unicode normalisation form that is applied to the text before any of its
+    /// queryable components are generated
+    ///
+    /// If not specified, texts are normalised to NFC.  See [`Normalisation`] for the details of
+    /// the choices available.  The normalisation form is stored in the ciphertext, and comparing
+    /// two texts that were normalised differently returns a `NormalisationMismatchError`.  The
+    /// same goes for equality and ordering queries, which record the normalisation form they were
+    /// generated with, so generate them with the same options as the stored texts (using
+    /// [`Text::query_equals_with_options()`](crate::datatype::Text::query_equals_with_options) and
+    /// [`Text::query_order_with_options()`](crate::datatype::Text::query_order_with_options)).
+    /// Prefix, substring, and word queries have nowhere to record it, so a query made with the
+    /// wrong normalisation form can't be detected, and just won't match anything.
+    ///
+    #[must_use]
+    pub fn with_normalisation(mut self, normalisation: Normalisation) -> Self {
+        self.normalisation = normalisation;
+        self
+    }
+
+    /// Include an ordering code, so that texts can be sorted and range-queried
+    ///
+    /// The `len` is the number of elements of the text's sort key that are encrypted; texts that
+    /// only differ after that many elements will compare as equal.  Ordering codes always contain
+    /// left ciphertexts, so they can only be included if unsafe parts are also enabled.
+    ///
+    #[must_use]
+    pub fn with_ordering(mut self, len: u8) -> Self {
+        self.ordering = Some(len);
+        self
+    }
+
+    /// Choose how texts are to be sorted when generating an ordering code
+    ///
+    /// If not specified, texts are sorted according to the default rules for English.  The
+    /// collation's identifier is stored in the ciphertext, and comparing two texts whose ordering
+    /// codes were generated with different collations is an error, so make sure you use the same
+    /// collation for a field's stored values as for any values you compare them against.
+    ///
+    /// This has no effect unless an ordering code is requested with
+    /// [`with_ordering()`](TextOptions::with_ordering).
+    ///
+    #[must_use]
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collator = Arc::new(collation);
+        self
+    }
+
+    /// Use a collator of your own devising when generating an ordering code
+    ///
+    /// This is for when none of the built-in [`Collation`]s sort texts the way you'd like them
+    /// sorted.  As with [`with_collation()`](TextOptions::with_collation), the collator's
+    /// [identifier](Collator::identifier) is stored in the ciphertext, and texts whose ordering
+    /// codes were generated by different collators cannot be compared.
+    ///
+    /// This has no effect unless an ordering code is requested with
+    /// [`with_ordering()`](TextOptions::with_ordering).
+    ///
+    #[must_use]
+    pub fn with_collator(mut self, collator: Arc<dyn Collator>) -> Self {
+        self.collator = collator;
+        self
+    }
+
+    /// Include a second equality ciphertext, which ignores differences in case and accents
+    ///
+    /// The text is converted into compatibility decomposed form (NFKD), lowercased, and stripped
+    /// of combining marks before being hashed, so that (for example) "Alice@Example.com" and
+    /// "alice@example.com" can be matched with
+    /// [`Text::query_equals_folded()`](crate::datatype::Text::query_equals_folded), without
+    /// needing to store a lowercased copy of the text.
+    ///
+    /// The folded ciphertext is stored in the same form as the regular equality ciphertext, so it
+    /// doesn't leak anything more than you'd expect: queries reveal which texts are "the same"
+    /// once case and accents are disregarded.
+    ///
+    #[must_use]
+    pub fn with_folded_equality(mut self) -> Self {
+        self.folded_equality = true;
+        self
+    }
+
+    /// Include a phonetic equality ciphertext, for finding names that sound alike
+    ///
+    /// Each word of the text is reduced to its [Soundex](https://en.wikipedia.org/wiki/Soundex)
+    /// code, so that (for example) "Kathryn Smyth" and "Katherine Smith" end up with the same
+    /// phonetic key, and the key is hashed and encrypted under its own field subkey.  The result
+    /// can be matched with
+    /// [`Text::query_sounds_like()`](crate::datatype::Text::query_sounds_like).
+    ///
+    /// Soundex is designed for English names written in the Latin alphabet, and always keeps the
+    /// first letter of each word, so "Catherine" and "Kathryn" don't match.  Accents are ignored,
+    /// but words written in other scripts don't contribute anything to the phonetic key at all.
+    ///
+    /// Like the other equality ciphertexts, this only leaks which texts sound alike when they are
+    /// queried.  Bear in mind, though, that a phonetic key is much less specific than the text
+    /// itself, so a query will typically match many more texts than an equality query would.
+    ///
+    #[must_use]
+    pub fn with_phonetic(mut self) -> Self {
+        self.phonetic = true;
+        self
+    }
+
+    /// Include prefix search tokens, so that texts can be searched for by their leading
+    /// characters (the equivalent of SQL's `LIKE 'abc%'`)
+    ///
+    /// A token is generated for each of the first `len` characters of the normalised text, and so
+    /// prefix queries longer than `len` characters will not match anything.  Each token adds
+    /// around 60 bytes to the ciphertext, or over 400 bytes if unsafe parts are included.
+    ///
+    #[must_use]
+    pub fn with_prefixes(mut self, len: u8) -> Self {
+        self.prefixes = Some(len);
+        self
+    }
+
+    /// Include substring search tokens, so that texts can be searched for fragments they contain
+    /// (the equivalent of SQL's `LIKE '%abc%'`)
+    ///
+    /// The text is broken up into every distinct run of three consecutive characters (its
+    /// "trigrams"), each of which is hashed with a field-specific key and then encrypted.  A
+    /// substring query is the same treatment applied to the search term, and matches any text
+    /// which contains *all* of the search term's trigrams.  That isn't quite the same thing as
+    /// containing the search term itself ("abcab" contains all the trigrams of "cabc", for
+    /// instance), so you need to check the decrypted values of the texts that match, to weed out
+    /// false positives.
+    ///
+    /// Substring tokens can only be included if unsafe parts are also enabled, because they
+    /// leak quite a lot:
+    ///
+    /// * The number of tokens is the number of distinct trigrams in the text, which is a fairly
+    ///   good approximation of the text's length.
+    ///
+    /// * Because the tokens contain left ciphertexts, anyone who can read the stored texts can
+    ///   tell which texts share trigrams with each other, and so can cluster texts by similarity
+    ///   and, with a decent guess at the sort of data being stored, run frequency analysis on the
+    ///   trigrams themselves.
+    ///
+    /// * Every substring query reveals which texts contain all of its trigrams.
+    ///
+    /// The storage overhead is also considerable: each trigram adds over 400 bytes to the
+    /// ciphertext.
+    ///
+    #[must_use]
+    pub fn with_substrings(mut self) -> Self {
+        self.substrings = true;
+        self
+    }
+
+    /// Include word tokens, so that texts can be searched for the words they contain
+    ///
+    /// The text is broken up into words according to the Unicode word boundary rules, ignoring
+    /// whitespace and punctuation, and each distinct word is lowercased, hashed with a
+    /// field-specific key, and then encrypted.  A word query, generated by
+    /// [`Text::query_word()`](crate::datatype::Text::query_word), matches any text which contains
+    /// that word, regardless of case.
+    ///
+    /// Word tokens can only be included if unsafe parts are also enabled, because the number of
+    /// tokens is the number of distinct words in the text, and because the tokens contain left
+    /// ciphertexts, anyone who can read the stored texts can tell which texts have words in
+    /// common.  Every word query also reveals which texts contain that word.  Each word adds over
+    /// 400 bytes to the ciphertext.
+    ///
+    #[must_use]
+    pub fn with_words(mut self) -> Self {
+        self.words = true;
+        self
+    }
+}
@@ -0,0 +1,159 @@
+//! Encrypted ordering codes, for range-querying texts
+//!
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use super::Normalisation;
+use crate::{
+    collator::LEGACY_COLLATION_IDENTIFIER,
+    crypto::OREv1,
+    datatype::kith::{Datatype as KithDatatype, Member as KithMember},
+    field::KeyId,
+    Error,
+};
+
+/// Versioning support
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[allow(clippy::missing_docs_in_private_items)] // I think we can all tell what's going on in here
+enum Ciphertext {
+    #[allow(non_camel_case_types)]
+    v1(Vec<OREv1<1, 256>>),
+    Unknown,
+}
+
+/// The encrypted ordering code of a text
+///
+/// This is what you get from [`Text::query_order()`](crate::datatype::Text::query_order), to
+/// compare against stored texts with
+/// [`Text::try_cmp_order()`](crate::datatype::Text::try_cmp_order), and from
+/// [`Text::order_code()`](crate::datatype::Text::order_code), if you'd like to do the comparison
+/// yourself.
+///
+/// As well as the order-revealing ciphertexts of each element of the text's sort key, an ordering
+/// code knows which collation and normalisation form were used to generate the sort key, so that
+/// texts which were sorted according to different rules can't be accidentally compared.
+///
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrderCode {
+    /// The ORE ciphertexts of the elements of the sort key
+    #[serde(rename = "o")]
+    ore_ciphertexts: Ciphertext,
+
+    /// The identifier of the collation used to generate the sort key, unless it's the one
+    /// that unlabelled ordering codes are assumed to have come from
+    #[serde(rename = "c")]
+    collation: Option<String>,
+
+    /// The normalisation form applied to the text before the sort key was generated, if it isn't
+    /// the default one
+    #[serde(rename = "n")]
+    normalisation: Option<Normalisation>,
+
+    /// The field key ID which was used to generate this ciphertext
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+impl OrderCode {
+    /// Create an ordering code from a sequence of `OREv1` ciphertexts
+    ///
+    pub(crate) fn from_ore_v1(
+        o: Vec<OREv1<1, 256>>,
+        collation: &str,
+        normalisation: Normalisation,
+        key_id: KeyId,
+    ) -> Self {
+        Self {
+            ore_ciphertexts: Ciphertext::v1(o),
+            collation: Some(collation.to_string()).filter(|c| c != LEGACY_COLLATION_IDENTIFIER),
+            normalisation: Some(normalisation).filter(|n| *n != Normalisation::default()),
+            kid: key_id.into(),
+        }
+    }
+
+    /// The identifier of the collation that was used to generate the ordering code
+    ///
+    #[must_use]
+    pub fn collation_identifier(&self) -> &str {
+        self.collation
+            .as_deref()
+            .unwrap_or(LEGACY_COLLATION_IDENTIFIER)
+    }
+
+    /// The normalisation form that was applied to the text before the ordering code was generated
+    ///
+    #[must_use]
+    pub fn normalisation(&self) -> Normalisation {
+        self.normalisation.unwrap_or_default()
+    }
+
+    /// Compare this ordering code with another
+    ///
+    /// Only as many elements of the sort key as are present in *both* ordering codes are
+    /// compared, so an ordering code of a text which was created with a shorter length than the
+    /// other compares as equal to anything which shares that many elements of its sort key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ordering codes were encrypted with different field keys, or
+    /// generated with different collations or normalisation forms, if neither ordering code
+    /// contains left ciphertexts, or if either ordering code is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lcoll = self.collation_identifier();
+        let rcoll = other.collation_identifier();
+        if lcoll != rcoll {
+            return Err(Error::CollationMismatchError(format!("{lcoll} vs {rcoll}")));
+        }
+
+        let lnorm = self.normalisation();
+        let rnorm = other.normalisation();
+        if lnorm != rnorm {
+            return Err(Error::NormalisationMismatchError(format!(
+                "{} vs {}",
+                lnorm.name(),
+                rnorm.name()
+            )));
+        }
+
+        match (&self.ore_ciphertexts, &other.ore_ciphertexts) {
+            (Ciphertext::v1(lhs), Ciphertext::v1(rhs)) => {
+                for (l, r) in lhs.iter().zip(rhs.iter()) {
+                    let o = l.try_cmp(r)?;
+                    if o.is_ne() {
+                        return Ok(o);
+                    }
+                }
+
+                Ok(Ordering::Equal)
+            }
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
+}
+
+impl KithMember for OrderCode {}
+
+impl KithDatatype for OrderCode {
+    fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self.ore_ciphertexts {
+            Ciphertext::v1(_) => 1,
+            Ciphertext::Unknown => 0,
+        }
+    }
+}
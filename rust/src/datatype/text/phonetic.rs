@@ -0,0 +1,115 @@
+//! Turn names into something that sounds about right
+//!
+//! This is an implementation of [American Soundex](https://en.wikipedia.org/wiki/Soundex), which
+//! is old, and English-centric, and not especially clever, but is well understood, and is what
+//! most people mean when they say "sounds like".
+//!
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Generate the phonetic key for a text
+///
+/// Each word of the text gets its own Soundex code, and the codes are joined together with
+/// spaces, so that "Jon Smyth" and "John Smith" both come out as `J500 S530`.  Accents are
+/// stripped before encoding, and words which don't contain any (unaccented) Latin letters are
+/// ignored entirely.
+///
+pub(crate) fn key(text: &str) -> String {
+    text.unicode_words()
+        .filter_map(soundex)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Calculate the Soundex code of a single word
+///
+fn soundex(word: &str) -> Option<String> {
+    let mut letters = word
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase());
+
+    let first = letters.next()?;
+    let mut code = String::from(first);
+    let mut last = digit(first);
+
+    for c in letters {
+        if code.len() == 4 {
+            break;
+        }
+
+        // H and W don't separate letters with the same code, so they're skipped entirely
+        let Some(d) = digit(c) else {
+            continue;
+        };
+
+        if d != '0' && Some(d) != last {
+            code.push(d);
+        }
+        last = Some(d);
+    }
+
+    while code.len() < 4 {
+        code.push('0');
+    }
+
+    Some(code)
+}
+
+/// The Soundex digit for an (uppercase ASCII) letter
+///
+/// Vowels (and Y) are `'0'`, which separates consonants but isn't included in the code, and H
+/// and W are `None`, because they don't even do that much.
+///
+fn digit(c: char) -> Option<char> {
+    match c {
+        'B' | 'F' | 'P' | 'V' => Some('1'),
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+        'D' | 'T' => Some('3'),
+        'L' => Some('4'),
+        'M' | 'N' => Some('5'),
+        'R' => Some('6'),
+        'H' | 'W' => None,
+        _ => Some('0'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soundex_codes() {
+        for (word, code) in [
+            ("Robert", "R163"),
+            ("Rupert", "R163"),
+            ("Rubin", "R150"),
+            ("Ashcraft", "A261"),
+            ("Ashcroft", "A261"),
+            ("Tymczak", "T522"),
+            ("Pfister", "P236"),
+            ("Lee", "L000"),
+        ] {
+            assert_eq!(Some(code.to_string()), soundex(word), "{word}");
+        }
+    }
+
+    #[test]
+    fn every_word_is_encoded() {
+        assert_eq!("J500 S530", key("Jon Smyth"));
+        assert_eq!(key("John Smith"), key("Jon Smyth"));
+    }
+
+    #[test]
+    fn accents_are_ignored() {
+        assert_eq!(key("Zoe Muller"), key("Zo\u{eb} M\u{fc}ller"));
+    }
+
+    #[test]
+    fn non_latin_words_are_ignored() {
+        assert_eq!("", key("\u{5f20}\u{4f1f}"));
+        assert_eq!("A350", key("Adam \u{5f20}"));
+    }
+}
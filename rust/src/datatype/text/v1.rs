@@ -5,11 +5,14 @@ use ciborium::cbor;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::hash::{Hash, Hasher};
-use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
 
+use super::{phonetic, Normalisation, TextOptions};
 use crate::{
-    collator,
+    collator::{Collator, LEGACY_COLLATION_IDENTIFIER},
     crypto::{AES256v1, EREv1, OREv1},
     field::KeyId,
     key_provider::{KeyProvider, Static},
@@ -19,7 +22,7 @@ use crate::{
 /// Version 1 of a Text value
 ///
 #[skip_serializing_none]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[doc(hidden)]
 pub struct V1 {
     /// The actual encrypted value
@@ -32,15 +35,41 @@ pub struct V1 {
     /// are equal
     #[serde(rename = "e")]
     equality_ciphertext: Option<EREv1<16, 16>>,
+    /// Like `equality_ciphertext`, but calculated over a case-folded and accent-stripped form of
+    /// the text, for case- and accent-insensitive equality
+    #[serde(rename = "f")]
+    folded_equality_ciphertext: Option<EREv1<16, 16>>,
+    /// Like `equality_ciphertext`, but calculated over the phonetic key of the text, for
+    /// sounds-like matching
+    #[serde(rename = "s")]
+    phonetic_ciphertext: Option<EREv1<16, 16>>,
     /// A truncated hash to help with indexing in large datasets
     #[serde(rename = "h")]
     hash_code: Option<u16>,
     /// A way to allow texts to be sorted without seeing the plaintext
     #[serde(rename = "o")]
     order_code: Option<Vec<OREv1<1, 256>>>,
+    /// The identifier of the collation used to generate the ordering code, unless it's the one
+    /// that unlabelled ordering codes are assumed to have come from
+    #[serde(rename = "c")]
+    collation: Option<String>,
+    /// The normalisation form applied to the text before its queryable parts were generated, if
+    /// it isn't the default one
+    #[serde(rename = "n")]
+    normalisation: Option<Normalisation>,
     /// The length of the text, in Unicode Scalar Values, and -- of course -- encrypted
     #[serde(rename = "l")]
     len: Option<OREv1<8, 16>>,
+    /// Equality ciphertexts of the leading characters of the text, for prefix searching
+    #[serde(rename = "p")]
+    prefixes: Option<Vec<EREv1<16, 16>>>,
+    /// Equality ciphertexts of every distinct three-character sequence in the text, for substring
+    /// searching
+    #[serde(rename = "t")]
+    trigrams: Option<Vec<EREv1<16, 16>>>,
+    /// Equality ciphertexts of every distinct word in the text, for keyword searching
+    #[serde(rename = "w")]
+    words: Option<Vec<EREv1<16, 16>>>,
     /// A serialisation-friendly form of the field key ID
     #[serde(rename = "k", with = "serde_bytes")]
     kid: Vec<u8>,
@@ -51,41 +80,104 @@ const TEXT_V1_EQUALITY_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.equality_hash_key";
 /// Identifier for the subkey used to encrypt the equality hash
 const TEXT_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] =
     b"TextV1.equality_hash_key_ciphertext";
+/// Identifier for the subkey used to calculate the plaintext value of the folded equality hash
+const TEXT_V1_FOLDED_EQUALITY_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.folded_equality_hash_key";
+/// Identifier for the subkey used to encrypt the folded equality hash
+const TEXT_V1_FOLDED_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] =
+    b"TextV1.folded_equality_hash_key_ciphertext";
+/// Identifier for the subkey used to calculate the plaintext value of the phonetic hash
+const TEXT_V1_PHONETIC_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.phonetic_hash_key";
+/// Identifier for the subkey used to encrypt the phonetic hash
+const TEXT_V1_PHONETIC_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] =
+    b"TextV1.phonetic_hash_key_ciphertext";
 /// Identifier for the subkey used to calculate the plaintext hash code
 const TEXT_V1_HASH_CODE_KEY_IDENTIFIER: &[u8] = b"TextV1.hash_code_key";
 /// Identifier for the subkey used to encrypt the ordering code
 const TEXT_V1_ORDER_CODE_KEY_IDENTIFIER: &[u8] = b"TextV1.order_code_key";
 /// Identifier for the subkey used to encrypt the text's length
 const TEXT_V1_LENGTH_KEY_IDENTIFIER: &[u8] = b"TextV1.length_key";
+/// Identifier for the subkey used to calculate the plaintext values of the prefix hashes
+const TEXT_V1_PREFIX_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.prefix_hash_key";
+/// Identifier for the subkey used to encrypt the prefix hashes
+const TEXT_V1_PREFIX_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] = b"TextV1.prefix_hash_key_ciphertext";
+/// Identifier for the subkey used to calculate the plaintext values of the trigram hashes
+const TEXT_V1_TRIGRAM_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.trigram_hash_key";
+/// Identifier for the subkey used to encrypt the trigram hashes
+const TEXT_V1_TRIGRAM_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] = b"TextV1.trigram_hash_key_ciphertext";
+/// Identifier for the subkey used to calculate the plaintext values of the word hashes
+const TEXT_V1_WORD_HASH_KEY_IDENTIFIER: &[u8] = b"TextV1.word_hash_key";
+/// Identifier for the subkey used to encrypt the word hashes
+const TEXT_V1_WORD_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] = b"TextV1.word_hash_key_ciphertext";
 
 impl V1 {
+    // New texts are always created as the latest version, so these constructors only exist so
+    // that the tests can get their hands on a complete V1 value.
+
     /// Make a new V1 ciphertext
     ///
+    #[cfg(test)]
     pub(crate) fn new(text: &str, context: &[u8], field: &Field) -> Result<V1, Error> {
-        Self::encrypt(text, context, field, false, None)
+        Self::encrypt(text, context, field, &TextOptions::new(), true)
     }
 
     /// Make a new V1 ciphertext with degraded security
     ///
+    #[cfg(test)]
     pub(crate) fn new_with_unsafe_parts(
         text: &str,
         context: &[u8],
         field: &Field,
         ordering: Option<u8>,
     ) -> Result<V1, Error> {
-        Self::encrypt(text, context, field, true, ordering)
+        let mut opts = TextOptions::new().with_unsafe_parts();
+        opts.ordering = ordering;
+
+        Self::encrypt(text, context, field, &opts, true)
+    }
+
+    /// Make a new V1 ciphertext with whatever optional components have been asked for
+    ///
+    #[cfg(test)]
+    pub(crate) fn new_with_options(
+        text: &str,
+        context: &[u8],
+        field: &Field,
+        opts: &TextOptions,
+    ) -> Result<V1, Error> {
+        Self::encrypt(text, context, field, opts, true)
     }
 
     /// Do the hard yards of actually creating the ciphertexts that make up the v1 `Text` value,
     /// and glueing them all together into the struct
     ///
-    fn encrypt(
+    /// The equality ciphertext is only included if `with_equality` is set; later versions of
+    /// `Text` which calculate equality differently leave it out, and supply their own.
+    ///
+    pub(crate) fn encrypt(
         text: &str,
         context: &[u8],
         field: &Field,
-        allow_unsafe: bool,
-        ordering: Option<u8>,
+        opts: &TextOptions,
+        with_equality: bool,
     ) -> Result<V1, Error> {
+        let allow_unsafe = opts.unsafe_parts;
+
+        if opts.ordering.is_some() && !allow_unsafe {
+            return Err(Error::OperationError(
+                "ordering codes can only be included in texts with unsafe parts".to_string(),
+            ));
+        }
+        if opts.substrings && !allow_unsafe {
+            return Err(Error::OperationError(
+                "substring tokens can only be included in texts with unsafe parts".to_string(),
+            ));
+        }
+        if opts.words && !allow_unsafe {
+            return Err(Error::OperationError(
+                "word tokens can only be included in texts with unsafe parts".to_string(),
+            ));
+        }
+
         let v = cbor!(text).map_err(|e| {
             Error::EncodingError(format!("failed to convert string to ciborium value: {e}"))
         })?;
@@ -96,10 +188,39 @@ impl V1 {
 
         let aes = AES256v1::new(&msg, context, field)?;
 
-        let normalised = text.nfc().collect::<String>();
+        let normalised = opts.normalisation.apply(text);
 
-        let eq_hash = Self::eq_hash(&normalised, field)?;
-        let eq = Self::ere_eq_hash(eq_hash, field, allow_unsafe)?;
+        #[allow(clippy::if_then_some_else_none)]
+        // Can't really use bool::then on a fallible function call
+        let eq = if with_equality {
+            Some(Self::ere_eq_hash(
+                Self::eq_hash(&normalised, field)?,
+                field,
+                allow_unsafe,
+            )?)
+        } else {
+            None
+        };
+
+        #[allow(clippy::if_then_some_else_none)]
+        // Can't really use bool::then on a fallible function call
+        let folded_eq = if opts.folded_equality {
+            Some(Self::ere_folded_equality(text, field, allow_unsafe)?)
+        } else {
+            None
+        };
+
+        #[allow(clippy::if_then_some_else_none)]
+        // Can't really use bool::then on a fallible function call
+        let phonetic = if opts.phonetic {
+            Some(Self::ere_phonetic(
+                &phonetic::key(&normalised),
+                field,
+                allow_unsafe,
+            )?)
+        } else {
+            None
+        };
 
         #[allow(clippy::if_then_some_else_none)]
         // Can't really use bool::then on a fallible function call
@@ -114,14 +235,47 @@ impl V1 {
         })?;
         let ore_len = Self::ore_length(pt_len, field, allow_unsafe)?;
 
-        let order_code = Self::order_code(&normalised, ordering, field)?;
+        let order_code = opts
+            .ordering
+            .map(|len| Self::order_code(&normalised, len, &*opts.collator, field))
+            .transpose()?;
+        let collation = Some(opts.collator.identifier())
+            .filter(|c| order_code.is_some() && c != LEGACY_COLLATION_IDENTIFIER);
+
+        let prefixes = opts
+            .prefixes
+            .map(|len| Self::prefixes_for(&normalised, len, field, allow_unsafe))
+            .transpose()?;
+
+        #[allow(clippy::if_then_some_else_none)]
+        // Can't really use bool::then on a fallible function call
+        let trigrams = if opts.substrings {
+            Some(Self::trigrams_for(&normalised, field, allow_unsafe)?)
+        } else {
+            None
+        };
+
+        #[allow(clippy::if_then_some_else_none)]
+        // Can't really use bool::then on a fallible function call
+        let words = if opts.words {
+            Some(Self::words_for(&normalised, field, allow_unsafe)?)
+        } else {
+            None
+        };
 
         Ok(V1 {
             aes_ciphertext: aes,
-            equality_ciphertext: Some(eq),
+            equality_ciphertext: eq,
+            folded_equality_ciphertext: folded_eq,
+            phonetic_ciphertext: phonetic,
             hash_code: hc,
             order_code,
+            collation,
+            normalisation: Some(opts.normalisation).filter(|n| *n != Normalisation::default()),
             len: Some(ore_len),
+            prefixes,
+            trigrams,
+            words,
             kid: field.key_id()?.into(),
         })
     }
@@ -142,8 +296,19 @@ impl V1 {
     ///
     pub(crate) fn make_unqueryable(&mut self) {
         self.equality_ciphertext = None;
+        self.folded_equality_ciphertext = None;
+        self.phonetic_ciphertext = None;
         self.hash_code = None;
         self.len = None;
+        self.prefixes = None;
+        self.trigrams = None;
+        self.words = None;
+    }
+
+    /// Remove the equality ciphertext from this `Text` value, and hand it over
+    ///
+    pub(crate) fn take_equality(&mut self) -> Option<EREv1<16, 16>> {
+        self.equality_ciphertext.take()
     }
 
     /// Return the field key ID that was used to create this `Text` value
@@ -160,6 +325,135 @@ impl V1 {
         self.len.clone()
     }
 
+    /// Return the ciphertext representing the equality hash of this `Text` value
+    ///
+    pub(crate) fn equality(&self) -> Option<EREv1<16, 16>> {
+        self.equality_ciphertext.clone()
+    }
+
+    /// Return the ciphertext representing the folded equality hash of this `Text` value
+    ///
+    pub(crate) fn folded_equality(&self) -> Option<EREv1<16, 16>> {
+        self.folded_equality_ciphertext.clone()
+    }
+
+    /// Return the ciphertext representing the phonetic hash of this `Text` value
+    ///
+    pub(crate) fn phonetic(&self) -> Option<EREv1<16, 16>> {
+        self.phonetic_ciphertext.clone()
+    }
+
+    /// Return the ciphertexts representing the prefixes of this `Text` value
+    ///
+    pub(crate) fn prefixes(&self) -> Option<Vec<EREv1<16, 16>>> {
+        self.prefixes.clone()
+    }
+
+    /// Return the ciphertexts representing the trigrams of this `Text` value
+    ///
+    pub(crate) fn trigrams(&self) -> Option<Vec<EREv1<16, 16>>> {
+        self.trigrams.clone()
+    }
+
+    /// Return the ciphertexts representing the words of this `Text` value
+    ///
+    pub(crate) fn words(&self) -> Option<Vec<EREv1<16, 16>>> {
+        self.words.clone()
+    }
+
+    /// Return the ciphertexts representing the ordering code of this `Text` value
+    ///
+    pub(crate) fn ordering(&self) -> Option<Vec<OREv1<1, 256>>> {
+        self.order_code.clone()
+    }
+
+    /// The identifier of the collation that was used to generate the ordering code
+    ///
+    pub(crate) fn collation_identifier(&self) -> &str {
+        self.collation
+            .as_deref()
+            .unwrap_or(LEGACY_COLLATION_IDENTIFIER)
+    }
+
+    /// The normalisation form that was applied to the text before its queryable parts were
+    /// generated
+    ///
+    pub(crate) fn normalisation(&self) -> Normalisation {
+        self.normalisation.unwrap_or_default()
+    }
+
+    /// Make sure that two texts were normalised the same way, since otherwise comparing them is
+    /// meaningless
+    ///
+    pub(crate) fn check_normalisation(&self, other: &Self) -> Result<(), Error> {
+        let lnorm = self.normalisation();
+        let rnorm = other.normalisation();
+
+        if lnorm == rnorm {
+            Ok(())
+        } else {
+            Err(Error::NormalisationMismatchError(format!(
+                "{} vs {}",
+                lnorm.name(),
+                rnorm.name()
+            )))
+        }
+    }
+
+    /// Compare the ordering codes of two texts
+    ///
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+        self.check_normalisation(other)?;
+
+        let lhs = self.order_code.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand text has no ordering code".to_string())
+        })?;
+        let rhs = other.order_code.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand text has no ordering code".to_string())
+        })?;
+
+        let lcoll = self.collation_identifier();
+        let rcoll = other.collation_identifier();
+        if lcoll != rcoll {
+            return Err(Error::CollationMismatchError(format!("{lcoll} vs {rcoll}")));
+        }
+
+        for (l, r) in lhs.iter().zip(rhs.iter()) {
+            let o = l.try_cmp(r)?;
+            if o.is_ne() {
+                return Ok(o);
+            }
+        }
+
+        Ok(lhs.len().cmp(&rhs.len()))
+    }
+
+    /// Determine whether two texts are equal
+    ///
+    /// While we'd ordinarily defer to `try_cmp() == Ordering::Equal`, in this case the order code
+    /// is "less accurate" than the equality ciphertext, and -- so far, at least -- you can only get
+    /// an orderable text value if you've also got an equality ciphertext, so there's no chance of
+    /// *having* to degrade.
+    ///
+    pub(crate) fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+        self.check_normalisation(other)?;
+
+        let lhs = self.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand text has no equality ciphertext".to_string())
+        })?;
+        let rhs = other.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand text has no equality ciphertext".to_string())
+        })?;
+
+        lhs.try_eq(rhs)
+    }
+
     /// Calculate the plaintext value of the equality hash
     ///
     /// This is a 64-bit value that, to a reasonable probability, uniquely identifies the text that
@@ -168,12 +462,18 @@ impl V1 {
     /// the harder for an attacker to try and figure out what texts are.
     ///
     fn eq_hash(text: &str, field: &Field) -> Result<u64, Error> {
+        Self::keyed_hash(text.as_bytes(), TEXT_V1_EQUALITY_HASH_KEY_IDENTIFIER, field)
+    }
+
+    /// Calculate a 64-bit keyed hash of some data, using the field subkey named by `identifier`
+    ///
+    pub(crate) fn keyed_hash(data: &[u8], identifier: &[u8], field: &Field) -> Result<u64, Error> {
         let mut hasher_key: [u8; 32] = Default::default();
-        field.subkey(&mut hasher_key, TEXT_V1_EQUALITY_HASH_KEY_IDENTIFIER)?;
+        field.subkey(&mut hasher_key, identifier)?;
 
         let hasher = Static::new(&hasher_key)?;
         let mut hash: [u8; 8] = Default::default();
-        hasher.derive_key(&mut hash, text.as_bytes())?;
+        hasher.derive_key(&mut hash, data)?;
 
         Ok(u64::from_be_bytes(hash))
     }
@@ -181,19 +481,324 @@ impl V1 {
     /// Encrypt the equality hash into an equality-revealing ciphertext
     ///
     fn ere_eq_hash(hc: u64, field: &Field, allow_unsafe: bool) -> Result<EREv1<16, 16>, Error> {
+        Self::ere_hash(
+            hc,
+            TEXT_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+            field,
+            allow_unsafe,
+        )
+    }
+
+    /// Encrypt a 64-bit hash into an equality-revealing ciphertext, using the field subkey named
+    /// by `identifier`
+    ///
+    fn ere_hash(
+        hash: u64,
+        identifier: &[u8],
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<EREv1<16, 16>, Error> {
         if allow_unsafe {
-            Ok(EREv1::<16, 16>::new_with_left(
-                hc,
-                TEXT_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
-                field,
-            )?)
+            Ok(EREv1::<16, 16>::new_with_left(hash, identifier, field)?)
         } else {
-            Ok(EREv1::<16, 16>::new(
-                hc,
-                TEXT_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
-                field,
-            )?)
+            Ok(EREv1::<16, 16>::new(hash, identifier, field)?)
+        }
+    }
+
+    /// Encrypt a given text as though it were a Text value's `equality_ciphertext`
+    ///
+    /// The text is normalised in exactly the same way as in `encrypt()`, so that queries match
+    /// stored values regardless of how the query text was composed.
+    ///
+    pub(crate) fn ere_equality(
+        text: &str,
+        normalisation: Normalisation,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<EREv1<16, 16>, Error> {
+        let normalised = normalisation.apply(text);
+
+        Self::ere_eq_hash(Self::eq_hash(&normalised, field)?, field, allow_unsafe)
+    }
+
+    /// Squash a text down into a form that ignores differences in case and accents
+    ///
+    /// The text is put into compatibility decomposed form (NFKD), lowercased, and then all the
+    /// combining marks (which is where the accents ended up, courtesy of the decomposition) are
+    /// thrown away.  So "Ågot", "ÅGOT", and "agot" all fold to the same thing.
+    ///
+    /// This uses Rust's idea of lowercase rather than full Unicode case folding, so a handful of
+    /// characters that case-fold to more than one character (such as "ß" vs "SS") won't match.
+    ///
+    fn fold(text: &str) -> String {
+        text.nfkd()
+            .flat_map(char::to_lowercase)
+            .filter(|c| !is_combining_mark(*c))
+            .collect()
+    }
+
+    /// Encrypt a given text as though it were a Text value's `folded_equality_ciphertext`
+    ///
+    pub(crate) fn ere_folded_equality(
+        text: &str,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<EREv1<16, 16>, Error> {
+        let hash = Self::keyed_hash(
+            Self::fold(text).as_bytes(),
+            TEXT_V1_FOLDED_EQUALITY_HASH_KEY_IDENTIFIER,
+            field,
+        )?;
+
+        Self::ere_hash(
+            hash,
+            TEXT_V1_FOLDED_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+            field,
+            allow_unsafe,
+        )
+    }
+
+    /// Encrypt a phonetic key as though it were a Text value's `phonetic_ciphertext`
+    ///
+    fn ere_phonetic(key: &str, field: &Field, allow_unsafe: bool) -> Result<EREv1<16, 16>, Error> {
+        let hash = Self::keyed_hash(key.as_bytes(), TEXT_V1_PHONETIC_HASH_KEY_IDENTIFIER, field)?;
+
+        Self::ere_hash(
+            hash,
+            TEXT_V1_PHONETIC_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+            field,
+            allow_unsafe,
+        )
+    }
+
+    /// Encrypt a given name as though it were a stored Text value's `phonetic_ciphertext`
+    ///
+    /// Used to generate sounds-like queries.  The name is normalised in the same way as the texts
+    /// being searched.
+    ///
+    pub(crate) fn ere_query_phonetic(name: &str, field: &Field) -> Result<EREv1<16, 16>, Error> {
+        let key = phonetic::key(&name.nfc().collect::<String>());
+
+        if key.is_empty() {
+            return Err(Error::RangeError(format!(
+                "{name:?} has nothing in it that can be phonetically encoded"
+            )));
+        }
+
+        Self::ere_phonetic(&key, field, true)
+    }
+
+    /// Encrypt a single prefix of a text into an equality-revealing ciphertext
+    ///
+    /// The number of characters in the prefix is mixed into the hash, so that the token for a
+    /// short text (which stands in for all of the prefixes longer than the text itself) can never
+    /// match a query for a longer prefix.
+    ///
+    fn ere_prefix(
+        prefix: &str,
+        char_count: u8,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<EREv1<16, 16>, Error> {
+        let mut data = Vec::with_capacity(prefix.len().saturating_add(1));
+        data.push(char_count);
+        data.extend_from_slice(prefix.as_bytes());
+
+        let hash = Self::keyed_hash(&data, TEXT_V1_PREFIX_HASH_KEY_IDENTIFIER, field)?;
+
+        Self::ere_hash(
+            hash,
+            TEXT_V1_PREFIX_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+            field,
+            allow_unsafe,
+        )
+    }
+
+    /// Generate the prefix ciphertexts for a (normalised) text
+    ///
+    /// Exactly `len` ciphertexts are always produced, regardless of the length of the text, so
+    /// that the number of them doesn't give away how long the text is.
+    ///
+    fn prefixes_for(
+        text: &str,
+        len: u8,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<Vec<EREv1<16, 16>>, Error> {
+        if len == 0 {
+            return Err(Error::RangeError(
+                "prefix length must be at least one character".to_string(),
+            ));
+        }
+
+        (1..=len)
+            .map(|n| {
+                let end = text
+                    .char_indices()
+                    .nth(n.into())
+                    .map_or(text.len(), |(i, _)| i);
+                let prefix = text.get(..end).ok_or_else(|| {
+                    Error::EncodingError(format!("could not take {n} character prefix of text"))
+                })?;
+
+                Self::ere_prefix(prefix, n, field, allow_unsafe)
+            })
+            .collect()
+    }
+
+    /// Encrypt a given text as though it were a prefix of a stored Text value
+    ///
+    /// Used to generate prefix search queries.  The prefix is normalised in the same way as the
+    /// texts being searched.
+    ///
+    pub(crate) fn ere_query_prefix(
+        prefix: &str,
+        normalisation: Normalisation,
+        field: &Field,
+    ) -> Result<EREv1<16, 16>, Error> {
+        let normalised = normalisation.apply(prefix);
+
+        let char_count = u8::try_from(normalised.chars().count()).map_err(|e| {
+            Error::RangeError(format!(
+                "prefix is longer than any prefix that is stored ({e})"
+            ))
+        })?;
+        if char_count == 0 {
+            return Err(Error::RangeError(
+                "cannot query for an empty prefix".to_string(),
+            ));
+        }
+
+        Self::ere_prefix(&normalised, char_count, field, true)
+    }
+
+    /// Calculate the (distinct) hashes of all the three-character sequences in a text
+    ///
+    /// The hashes are returned in ascending numeric order, rather than the order in which the
+    /// trigrams appear, so that the position of each trigram in the text isn't given away.
+    ///
+    fn trigram_hashes(text: &str, field: &Field) -> Result<BTreeSet<u64>, Error> {
+        let chars = text.chars().collect::<Vec<_>>();
+
+        chars
+            .windows(3)
+            .map(|w| {
+                Self::keyed_hash(
+                    w.iter().collect::<String>().as_bytes(),
+                    TEXT_V1_TRIGRAM_HASH_KEY_IDENTIFIER,
+                    field,
+                )
+            })
+            .collect()
+    }
+
+    /// Generate the trigram ciphertexts for a (normalised) text
+    ///
+    fn trigrams_for(
+        text: &str,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<Vec<EREv1<16, 16>>, Error> {
+        Self::trigram_hashes(text, field)?
+            .into_iter()
+            .map(|h| {
+                Self::ere_hash(
+                    h,
+                    TEXT_V1_TRIGRAM_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+                    field,
+                    allow_unsafe,
+                )
+            })
+            .collect()
+    }
+
+    /// Encrypt the trigrams of a given text as though they were from a stored Text value
+    ///
+    /// Used to generate substring search queries.  The needle is normalised in the same way as the
+    /// texts being searched.
+    ///
+    pub(crate) fn ere_query_trigrams(
+        needle: &str,
+        normalisation: Normalisation,
+        field: &Field,
+    ) -> Result<Vec<EREv1<16, 16>>, Error> {
+        let normalised = normalisation.apply(needle);
+
+        if normalised.chars().count() < 3 {
+            return Err(Error::RangeError(
+                "substring queries must be at least three characters long".to_string(),
+            ));
         }
+
+        Self::trigrams_for(&normalised, field, true)
+    }
+
+    /// Break a (normalised) text into its words, in the form in which they're hashed
+    ///
+    /// Words are found using the Unicode word boundary rules (UAX #29), and anything that isn't
+    /// a word (whitespace, punctuation, and the like) is discarded.  Words are lowercased, so
+    /// that a keyword search doesn't care whether the word was at the start of a sentence.
+    ///
+    fn word_list(text: &str) -> Vec<String> {
+        text.unicode_words().map(str::to_lowercase).collect()
+    }
+
+    /// Hash a single (already lowercased) word
+    ///
+    fn word_hash(word: &str, field: &Field) -> Result<u64, Error> {
+        Self::keyed_hash(word.as_bytes(), TEXT_V1_WORD_HASH_KEY_IDENTIFIER, field)
+    }
+
+    /// Generate the word ciphertexts for a (normalised) text
+    ///
+    /// As with trigrams, each distinct word only gets one ciphertext, and the ciphertexts are
+    /// sorted by hash value rather than by the position of the word in the text.
+    ///
+    fn words_for(
+        text: &str,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<Vec<EREv1<16, 16>>, Error> {
+        Self::word_list(text)
+            .iter()
+            .map(|w| Self::word_hash(w, field))
+            .collect::<Result<BTreeSet<_>, _>>()?
+            .into_iter()
+            .map(|h| {
+                Self::ere_hash(
+                    h,
+                    TEXT_V1_WORD_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+                    field,
+                    allow_unsafe,
+                )
+            })
+            .collect()
+    }
+
+    /// Encrypt a given word as though it were one of the words of a stored Text value
+    ///
+    /// Used to generate keyword search queries.  The word is normalised in the same way as the
+    /// texts being searched.
+    ///
+    pub(crate) fn ere_query_word(
+        word: &str,
+        normalisation: Normalisation,
+        field: &Field,
+    ) -> Result<EREv1<16, 16>, Error> {
+        let normalised = normalisation.apply(word);
+
+        let [ref w] = Self::word_list(&normalised)[..] else {
+            return Err(Error::RangeError(format!(
+                "word queries must contain exactly one word (got {word:?})"
+            )));
+        };
+
+        Self::ere_hash(
+            Self::word_hash(w, field)?,
+            TEXT_V1_WORD_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+            field,
+            true,
+        )
     }
 
     /// Encrypt a given number as though it were a Text value's `length`
@@ -252,32 +857,53 @@ impl V1 {
     /// (ICU bumps its soname like there's a prize for the highest version number).
     ///
     /// All this is to say that, by default, there's a built-in collator that just returns ASCII
-    /// values for the ordering code, but that can be swapped out by building with the `icu` feature.
+    /// values for the ordering code, but that can be swapped out by building with the `icu` feature,
+    /// or by supplying a `collator` of your own.
     ///
     fn order_code(
         text: &str,
-        ordering: Option<u8>,
+        len: u8,
+        collator: &dyn Collator,
         field: &Field,
-    ) -> Result<Option<Vec<OREv1<1, 256>>>, Error> {
-        match ordering {
-            None => Ok(None),
-            Some(len) => {
-                let sort_key = collator::generate_sort_key(text, "en")?;
-
-                let mut order_vec: Vec<OREv1<1, 256>> = vec![];
-
-                for i in 0..len {
-                    let sort_key_component = sort_key.get(i as usize).unwrap_or(&0);
-                    let v = OREv1::<1, 256>::new_with_left(
-                        *sort_key_component,
-                        TEXT_V1_ORDER_CODE_KEY_IDENTIFIER,
-                        field,
-                    )?;
-                    order_vec.push(v);
-                }
-                Ok(Some(order_vec))
-            }
+    ) -> Result<Vec<OREv1<1, 256>>, Error> {
+        let sort_key = collator.sort_key(text)?;
+
+        let mut order_vec: Vec<OREv1<1, 256>> = vec![];
+
+        for i in 0..len {
+            let sort_key_component = sort_key.get(i as usize).unwrap_or(&0);
+            let v = OREv1::<1, 256>::new_with_left(
+                *sort_key_component,
+                TEXT_V1_ORDER_CODE_KEY_IDENTIFIER,
+                field,
+            )?;
+            order_vec.push(v);
         }
+
+        Ok(order_vec)
+    }
+
+    /// Generate the ordering code for a given text, for comparing against stored Text values
+    ///
+    /// Used to generate range queries.  The text is normalised in the same way as the texts being
+    /// compared against.
+    ///
+    pub(crate) fn ore_query_order(
+        text: &str,
+        len: u8,
+        collator: &dyn Collator,
+        normalisation: Normalisation,
+        field: &Field,
+    ) -> Result<Vec<OREv1<1, 256>>, Error> {
+        if len == 0 {
+            return Err(Error::RangeError(
+                "cannot query with an empty ordering code".to_string(),
+            ));
+        }
+
+        let normalised = normalisation.apply(text);
+
+        Self::order_code(&normalised, len, collator, field)
     }
 }
 
@@ -291,23 +917,9 @@ impl Hash for V1 {
 }
 
 impl Ord for V1 {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to signal error from impl Ord
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
     fn cmp(&self, other: &Self) -> Ordering {
-        assert!(
-            self.kid == other.kid,
-            "Cannot compare ciphertexts from different keys"
-        );
-
-        let lhs = self
-            .order_code
-            .as_ref()
-            .expect("Cannot compare without an ordering code on the left-hand side");
-        let rhs = other
-            .order_code
-            .as_ref()
-            .expect("Cannot compare without an ordering code on the right-hand side");
-
-        lhs.cmp(rhs)
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -318,25 +930,9 @@ impl PartialOrd for V1 {
 }
 
 impl PartialEq for V1 {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to signal error from impl Eq
+    #[allow(clippy::panic)] // No way to signal error from impl Eq
     fn eq(&self, other: &Self) -> bool {
-        assert!(
-            self.kid == other.kid,
-            "Cannot compare ciphertexts from different keys"
-        );
-
-        // While we'd ordinarily defer to cmp == Ordering::Equal in a PartialEq
-        // implementation, in this case the order code is "less accurate"
-        // than the equality ciphertext, and -- so far, at least -- you
-        // can only get an orderable text value if you've also got an equality
-        // ciphertext, so there's no chance of *having* to degrade.
-        self.equality_ciphertext
-            .as_ref()
-            .expect("Cannot compare text values without LHS equality_ciphertext")
-            == other
-                .equality_ciphertext
-                .as_ref()
-                .expect("Cannot compare text values without RHS equality_ciphertext")
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -345,7 +941,12 @@ impl Eq for V1 {}
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{crypto::OREv1, key_provider::Static, Root};
+    use crate::{
+        collator::{Bytes, Collation, Strength},
+        crypto::OREv1,
+        key_provider::Static,
+        Root,
+    };
     use std::sync::Arc;
 
     fn field() -> Field {
@@ -386,6 +987,40 @@ mod tests {
         assert_ne!(text1, text3);
     }
 
+    #[test]
+    fn comparison_errors_are_reported() {
+        let other_field = Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"baz")
+        .unwrap();
+
+        let text = V1::new_with_unsafe_parts("ohai!", b"", &field(), Some(8)).unwrap();
+        let other_key = V1::new_with_unsafe_parts("ohai!", b"", &other_field, Some(8)).unwrap();
+        let safe = V1::new("ohai!", b"", &field()).unwrap();
+        let mut unqueryable = V1::new_with_unsafe_parts("ohai!", b"", &field(), Some(8)).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            text.try_eq(&other_key),
+            Err(Error::KeyMismatchError())
+        ));
+        assert!(matches!(
+            text.try_cmp(&safe),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert!(matches!(
+            safe.try_eq(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            text.try_eq(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert!(text.try_eq(&safe).unwrap());
+    }
+
     #[test]
     fn hash_codes_compare_correctly() {
         let text1 = V1::new_with_unsafe_parts("Hello, Enquo!", b"", &field(), None).unwrap();
@@ -414,6 +1049,43 @@ mod tests {
     mod icu_collation {
         use super::*;
 
+        fn ordered(text: &str, collation: &Collation) -> V1 {
+            let opts = TextOptions::new()
+                .with_unsafe_parts()
+                .with_ordering(8)
+                .with_collation(collation.clone());
+            V1::new_with_options(text, b"", &field(), &opts).unwrap()
+        }
+
+        #[test]
+        fn collation_locale_is_respected() {
+            let de = Collation::new("de");
+            let sv = Collation::new("sv");
+
+            // German sorts "ä" as a variant of "a", Swedish puts it after "z"
+            assert!(ordered("\u{e4}r", &de) < ordered("zu", &de));
+            assert!(ordered("\u{e4}r", &sv) > ordered("zu", &sv));
+        }
+
+        #[test]
+        fn collation_strength_is_respected() {
+            let primary = Collation::new("en").with_strength(Strength::Primary);
+            let tertiary = Collation::new("en").with_strength(Strength::Tertiary);
+
+            assert_eq!(
+                Ordering::Equal,
+                ordered("B", &primary)
+                    .try_cmp(&ordered("b", &primary))
+                    .unwrap()
+            );
+            assert_ne!(
+                Ordering::Equal,
+                ordered("B", &tertiary)
+                    .try_cmp(&ordered("b", &tertiary))
+                    .unwrap()
+            );
+        }
+
         #[test]
         fn accented_orderable_strings_compare_correctly() {
             let first = V1::new_with_unsafe_parts(
@@ -429,7 +1101,39 @@ mod tests {
         }
     }
 
-    #[cfg(not(feature = "icu"))]
+    #[cfg(all(feature = "uca", not(feature = "icu")))]
+    mod uca_collation {
+        use super::*;
+
+        #[test]
+        fn accented_orderable_strings_compare_correctly() {
+            let first = V1::new_with_unsafe_parts(
+                &String::from_utf8(b"R\xCC\x83amone".to_vec()).unwrap(),
+                b"",
+                &field(),
+                Some(8),
+            )
+            .unwrap();
+            let second = V1::new_with_unsafe_parts("Roman", b"", &field(), Some(8)).unwrap();
+
+            assert!(first < second);
+        }
+
+        #[test]
+        fn tailored_collations_are_not_supported() {
+            let opts = TextOptions::new()
+                .with_unsafe_parts()
+                .with_ordering(8)
+                .with_collation(Collation::new("sv"));
+
+            assert!(matches!(
+                V1::new_with_options("one", b"", &field(), &opts),
+                Err(Error::CollationError(_))
+            ));
+        }
+    }
+
+    #[cfg(not(any(feature = "icu", feature = "uca")))]
     mod lexicographic_collation {
         use super::*;
 
@@ -448,6 +1152,168 @@ mod tests {
         }
     }
 
+    #[cfg(any(feature = "icu", not(feature = "uca")))]
+    #[test]
+    fn default_collation_is_not_recorded() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let value = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+
+        assert!(value.collation.is_none());
+        assert_eq!(LEGACY_COLLATION_IDENTIFIER, value.collation_identifier());
+    }
+
+    #[cfg(any(feature = "icu", not(feature = "uca")))]
+    #[test]
+    fn legacy_ordering_codes_compare_with_new_ones() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let new = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+
+        // Ordering codes stored before the collation was recorded have no "c" in them at all
+        let mut legacy = V1::new_with_options("two", b"", &field(), &opts).unwrap();
+        legacy.collation = None;
+
+        assert_eq!(Ordering::Less, new.try_cmp(&legacy).unwrap());
+    }
+
+    /// Stands in for the ordering codes stored before the collation engine was recorded
+    #[cfg(all(feature = "uca", not(feature = "icu")))]
+    #[derive(Debug)]
+    struct LegacyCollator;
+
+    #[cfg(all(feature = "uca", not(feature = "icu")))]
+    impl Collator for LegacyCollator {
+        fn identifier(&self) -> String {
+            LEGACY_COLLATION_IDENTIFIER.to_string()
+        }
+
+        fn sort_key(&self, text: &str) -> Result<Vec<u8>, Error> {
+            Ok(text.as_bytes().to_vec())
+        }
+    }
+
+    #[cfg(all(feature = "uca", not(feature = "icu")))]
+    #[test]
+    fn legacy_ordering_codes_are_not_mistaken_for_uca_ones() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let uca = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+        let legacy = V1::new_with_options(
+            "two",
+            b"",
+            &field(),
+            &opts.with_collator(Arc::new(LegacyCollator)),
+        )
+        .unwrap();
+
+        assert!(legacy.collation.is_none());
+        assert!(matches!(
+            uca.try_cmp(&legacy),
+            Err(Error::CollationMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn collation_is_recorded() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_collation(Collation::new("en-AU").with_strength(Strength::Primary));
+        let value = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+
+        assert!(value.collation_identifier().ends_with("en-AU/primary"));
+    }
+
+    #[test]
+    fn collation_is_only_recorded_with_ordering() {
+        let opts = TextOptions::new().with_collation(Collation::new("sv"));
+        let value = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+
+        assert!(value.collation.is_none());
+    }
+
+    #[test]
+    fn mismatched_collations_cannot_be_compared() {
+        let opts = TextOptions::new().with_unsafe_parts().with_ordering(8);
+        let en = V1::new_with_options("one", b"", &field(), &opts).unwrap();
+        let root = V1::new_with_options(
+            "two",
+            b"",
+            &field(),
+            &opts.clone().with_collation(Collation::new("root")),
+        )
+        .unwrap();
+        let root2 = V1::new_with_options(
+            "three",
+            b"",
+            &field(),
+            &opts.with_collation(Collation::new("root")),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            en.try_cmp(&root),
+            Err(Error::CollationMismatchError(_))
+        ));
+        assert_eq!(Ordering::Greater, root.try_cmp(&root2).unwrap());
+    }
+
+    /// Sorts runs of digits by their numeric value, so "A9" comes before "A10"
+    #[derive(Debug)]
+    struct NaturalCollator;
+
+    impl Collator for NaturalCollator {
+        fn identifier(&self) -> String {
+            "natural".to_string()
+        }
+
+        fn sort_key(&self, text: &str) -> Result<Vec<u8>, Error> {
+            let mut key = vec![];
+            let mut digits = String::new();
+
+            for c in text.chars().chain(std::iter::once('\0')) {
+                if c.is_ascii_digit() {
+                    digits.push(c);
+                    continue;
+                }
+                if !digits.is_empty() {
+                    key.push(u8::try_from(digits.len()).unwrap());
+                    key.extend(digits.bytes());
+                    digits.clear();
+                }
+                if c != '\0' {
+                    key.extend(c.to_string().bytes());
+                }
+            }
+
+            Ok(key)
+        }
+    }
+
+    #[test]
+    fn custom_collator_is_used() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_collator(Arc::new(NaturalCollator));
+        let a9 = V1::new_with_options("A9", b"", &field(), &opts).unwrap();
+        let a10 = V1::new_with_options("A10", b"", &field(), &opts).unwrap();
+
+        assert_eq!("natural", a9.collation_identifier());
+        assert_eq!(Ordering::Less, a9.try_cmp(&a10).unwrap());
+    }
+
+    #[test]
+    fn byte_collator_is_used() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_collator(Arc::new(Bytes));
+        let upper = V1::new_with_options("Zed", b"", &field(), &opts).unwrap();
+        let lower = V1::new_with_options("alpha", b"", &field(), &opts).unwrap();
+
+        assert_eq!("bytes", upper.collation_identifier());
+        assert_eq!(Ordering::Less, upper.try_cmp(&lower).unwrap());
+    }
+
     #[test]
     fn minimum_serialised_ciphertext_size() {
         let value = V1::new("", b"somecontext", &field()).unwrap();
@@ -525,6 +1391,334 @@ mod tests {
         assert_eq!(non_normalised, normalised);
     }
 
+    #[test]
+    fn equality_queries_match_stored_values() {
+        let value = V1::new(
+            &String::from_utf8(b"La Nin\xCC\x83a".to_vec()).unwrap(),
+            b"somecontext",
+            &field(),
+        )
+        .unwrap();
+        let query = V1::ere_equality(
+            &String::from_utf8(b"La Ni\xC3\xB1a".to_vec()).unwrap(),
+            Normalisation::Nfc,
+            &field(),
+            true,
+        )
+        .unwrap();
+        let other = V1::ere_equality("La Nina", Normalisation::Nfc, &field(), true).unwrap();
+
+        assert!(query.try_eq(&value.equality().unwrap()).unwrap());
+        assert!(!other.try_eq(&value.equality().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn folded_equality_ignores_case_and_accents() {
+        let opts = TextOptions::new().with_folded_equality();
+        let value = V1::new_with_options("Alice@Example.com", b"", &field(), &opts).unwrap();
+        let folded = value.folded_equality().unwrap();
+
+        assert!(!folded.has_left());
+
+        let matches = |q: &str| {
+            V1::ere_folded_equality(q, &field(), true)
+                .unwrap()
+                .try_eq(&folded)
+                .unwrap()
+        };
+
+        assert!(matches("alice@example.com"));
+        assert!(matches("ALICE@EXAMPLE.COM"));
+        assert!(matches(
+            &String::from_utf8(b"\xC3\x81lice@example.com".to_vec()).unwrap()
+        ));
+        assert!(matches(
+            &String::from_utf8(b"A\xCC\x81lice@example.com".to_vec()).unwrap()
+        ));
+        assert!(!matches("alice@example.org"));
+    }
+
+    #[test]
+    fn folded_equality_is_opt_in() {
+        let value = V1::new("Alice", b"", &field()).unwrap();
+
+        assert!(value.folded_equality().is_none());
+    }
+
+    #[test]
+    fn prefixes_are_opt_in() {
+        let value = V1::new("ohai!", b"somecontext", &field()).unwrap();
+
+        assert!(value.prefixes().is_none());
+    }
+
+    #[test]
+    fn prefix_queries_match_stored_prefixes() {
+        let value = V1::new_with_options(
+            "Hello, Enquo!",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_prefixes(4),
+        )
+        .unwrap();
+        let prefixes = value.prefixes().unwrap();
+
+        assert_eq!(4, prefixes.len());
+        assert!(!prefixes[0].has_left());
+
+        let matches = |q: &str| {
+            let query = V1::ere_query_prefix(q, Normalisation::Nfc, &field()).unwrap();
+            prefixes.iter().any(|p| p.try_eq(&query).unwrap())
+        };
+
+        assert!(matches("H"));
+        assert!(matches("Hel"));
+        assert!(matches("Hell"));
+        assert!(!matches("Hello"));
+        assert!(!matches("hell"));
+        assert!(!matches("Help"));
+    }
+
+    #[test]
+    fn short_texts_still_have_all_the_prefixes() {
+        let value = V1::new_with_options(
+            "Hi",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_prefixes(4),
+        )
+        .unwrap();
+        let prefixes = value.prefixes().unwrap();
+
+        assert_eq!(4, prefixes.len());
+
+        let hi = V1::ere_query_prefix("Hi", Normalisation::Nfc, &field()).unwrap();
+        let hit = V1::ere_query_prefix("Hit", Normalisation::Nfc, &field()).unwrap();
+
+        assert!(prefixes.iter().any(|p| p.try_eq(&hi).unwrap()));
+        assert!(!prefixes.iter().any(|p| p.try_eq(&hit).unwrap()));
+    }
+
+    #[test]
+    fn prefix_queries_use_normalised_text() {
+        let value = V1::new_with_options(
+            &String::from_utf8(b"Nin\xCC\x83a".to_vec()).unwrap(),
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_prefixes(3),
+        )
+        .unwrap();
+        let query = V1::ere_query_prefix(
+            &String::from_utf8(b"Ni\xC3\xB1".to_vec()).unwrap(),
+            Normalisation::Nfc,
+            &field(),
+        )
+        .unwrap();
+
+        assert!(value.prefixes().unwrap()[2].try_eq(&query).unwrap());
+    }
+
+    #[test]
+    fn substring_queries_match_stored_trigrams() {
+        let value = V1::new_with_options(
+            "abracadabra",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_unsafe_parts().with_substrings(),
+        )
+        .unwrap();
+        let trigrams = value.trigrams().unwrap();
+
+        // abr, bra, rac, aca, cad, ada, dab
+        assert_eq!(7, trigrams.len());
+
+        let matches = |q: &str| {
+            V1::ere_query_trigrams(q, Normalisation::Nfc, &field())
+                .unwrap()
+                .iter()
+                .all(|q| trigrams.iter().any(|t| t.try_eq(q).unwrap()))
+        };
+
+        assert!(matches("abra"));
+        assert!(matches("cad"));
+        assert!(matches("dabra"));
+        assert!(!matches("cab"));
+        assert!(!matches("abracadabrax"));
+    }
+
+    #[test]
+    fn substrings_require_unsafe_parts() {
+        let err = V1::new_with_options(
+            "ohai!",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_substrings(),
+        )
+        .err();
+
+        assert!(matches!(err, Some(Error::OperationError(_))));
+    }
+
+    #[test]
+    fn substring_queries_must_be_long_enough() {
+        assert!(matches!(
+            V1::ere_query_trigrams("ab", Normalisation::Nfc, &field()).err(),
+            Some(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn word_queries_match_stored_words() {
+        let value = V1::new_with_options(
+            "Don't panic!  Don't forget your towel.",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_unsafe_parts().with_words(),
+        )
+        .unwrap();
+        let words = value.words().unwrap();
+
+        // don't, panic, forget, your, towel
+        assert_eq!(5, words.len());
+
+        let matches = |q: &str| {
+            let q = V1::ere_query_word(q, Normalisation::Nfc, &field()).unwrap();
+            words.iter().any(|w| w.try_eq(&q).unwrap())
+        };
+
+        assert!(matches("don't"));
+        assert!(matches("TOWEL"));
+        assert!(!matches("tow"));
+        assert!(!matches("don"));
+    }
+
+    #[test]
+    fn words_require_unsafe_parts() {
+        let err = V1::new_with_options(
+            "ohai!",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_words(),
+        )
+        .err();
+
+        assert!(matches!(err, Some(Error::OperationError(_))));
+    }
+
+    #[test]
+    fn word_queries_must_be_one_word() {
+        assert!(matches!(
+            V1::ere_query_word("two words", Normalisation::Nfc, &field()).err(),
+            Some(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            V1::ere_query_word("...", Normalisation::Nfc, &field()).err(),
+            Some(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn phonetic_queries_match_similar_names() {
+        let value = V1::new_with_options(
+            "Katherine Smith",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_phonetic(),
+        )
+        .unwrap();
+        let phonetic = value.phonetic().unwrap();
+
+        let matches = |q: &str| {
+            phonetic
+                .try_eq(&V1::ere_query_phonetic(q, &field()).unwrap())
+                .unwrap()
+        };
+
+        assert!(matches("Kathryn Smyth"));
+        assert!(!matches("Kathryn"));
+        assert!(!matches("Kathryn Jones"));
+    }
+
+    #[test]
+    fn phonetic_queries_need_something_to_encode() {
+        assert!(matches!(
+            V1::ere_query_phonetic("123", &field()).err(),
+            Some(Error::RangeError(_))
+        ));
+    }
+
+    fn with_normalisation(text: &str, normalisation: Normalisation) -> V1 {
+        V1::new_with_options(
+            text,
+            b"somecontext",
+            &field(),
+            &TextOptions::new()
+                .with_unsafe_parts()
+                .with_ordering(8)
+                .with_normalisation(normalisation),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn compatibility_normalisation_folds_full_width_digits() {
+        assert_eq!(
+            with_normalisation("\u{ff11}\u{ff12}\u{ff13}", Normalisation::Nfkc),
+            with_normalisation("123", Normalisation::Nfkc)
+        );
+        assert_ne!(
+            with_normalisation("\u{ff11}\u{ff12}\u{ff13}", Normalisation::Nfc),
+            with_normalisation("123", Normalisation::Nfc)
+        );
+    }
+
+    #[test]
+    fn verbatim_texts_are_not_normalised() {
+        assert_ne!(
+            with_normalisation("Nin\u{303}a", Normalisation::Verbatim),
+            with_normalisation("Ni\u{f1}a", Normalisation::Verbatim)
+        );
+    }
+
+    #[test]
+    fn default_normalisation_is_not_recorded() {
+        assert!(with_normalisation("one", Normalisation::Nfc)
+            .normalisation
+            .is_none());
+        assert_eq!(
+            Some(Normalisation::Nfkc),
+            with_normalisation("one", Normalisation::Nfkc).normalisation
+        );
+    }
+
+    #[test]
+    fn mismatched_normalisations_cannot_be_compared() {
+        let nfc = with_normalisation("one", Normalisation::Nfc);
+        let nfkc = with_normalisation("one", Normalisation::Nfkc);
+
+        assert!(matches!(
+            nfc.try_eq(&nfkc),
+            Err(Error::NormalisationMismatchError(_))
+        ));
+        assert!(matches!(
+            nfc.try_cmp(&nfkc),
+            Err(Error::NormalisationMismatchError(_))
+        ));
+    }
+
+    #[test]
+    fn ordering_requires_unsafe_parts() {
+        let err = V1::new_with_options(
+            "ohai!",
+            b"somecontext",
+            &field(),
+            &TextOptions::new().with_ordering(8),
+        )
+        .err();
+
+        assert!(matches!(err, Some(Error::OperationError(_))));
+    }
+
     #[test]
     fn hash_codes_use_normalised_text() {
         let non_normalised = V1::new_with_unsafe_parts(
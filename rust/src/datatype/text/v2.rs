@@ -0,0 +1,295 @@
+//! Version 2 of the Text datatype
+//!
+//! The only difference from version 1 is the equality ciphertext, which is calculated over a
+//! 128-bit keyed hash of the text, rather than a 64-bit one.  Everything else is delegated to a
+//! version 1 value, which is created without an equality ciphertext of its own.
+//!
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use super::{v1::V1, Normalisation, TextOptions};
+use crate::{
+    crypto::EREv1,
+    key_provider::{KeyProvider, Static},
+    Error, Field,
+};
+
+/// Version 2 of a Text value
+///
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V2 {
+    /// All the parts of the text which haven't changed since version 1
+    #[serde(flatten)]
+    base: V1,
+    /// How we can tell, with a very high degree of certainty, whether or not two encrypted texts
+    /// are equal
+    #[serde(rename = "e")]
+    equality_ciphertext: Option<EREv1<32, 16>>,
+}
+
+/// Identifier for the subkey used to calculate the plaintext value of the equality hash
+const TEXT_V2_EQUALITY_HASH_KEY_IDENTIFIER: &[u8] = b"TextV2.equality_hash_key";
+/// Identifier for the subkey used to encrypt the equality hash
+const TEXT_V2_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] =
+    b"TextV2.equality_hash_key_ciphertext";
+
+impl V2 {
+    /// Create a new v2 Text value with the given options
+    ///
+    pub(crate) fn new_with_options(
+        text: &str,
+        context: &[u8],
+        field: &Field,
+        opts: &TextOptions,
+    ) -> Result<V2, Error> {
+        let base = V1::encrypt(text, context, field, opts, false)?;
+        let eq = Self::ere_equality(text, opts.normalisation, field, opts.unsafe_parts)?;
+
+        Ok(V2 {
+            base,
+            equality_ciphertext: Some(eq),
+        })
+    }
+
+    /// Upgrade a v1 Text value to v2
+    ///
+    /// Everything except the equality ciphertext is carried over as-is; the equality ciphertext
+    /// is recalculated from the decrypted text, and only includes a left part if the v1 one did.
+    /// A text which had no equality ciphertext doesn't get one now, either.
+    ///
+    pub(crate) fn from_v1(v1: &V1, context: &[u8], field: &Field) -> Result<V2, Error> {
+        let mut base = v1.clone();
+        let equality_ciphertext = match base.take_equality() {
+            Some(v1_eq) => Some(Self::ere_equality(
+                &base.decrypt(context, field)?,
+                base.normalisation(),
+                field,
+                v1_eq.has_left(),
+            )?),
+            None => None,
+        };
+
+        Ok(V2 {
+            base,
+            equality_ciphertext,
+        })
+    }
+
+    /// The parts of the text which are shared with version 1
+    ///
+    pub(crate) fn base(&self) -> &V1 {
+        &self.base
+    }
+
+    /// Strip all the mass of data that allows the text to be queried, leaving just the encrypted
+    /// value that can be read
+    ///
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.base.make_unqueryable();
+        self.equality_ciphertext = None;
+    }
+
+    /// Return the ciphertext representing the equality hash of this `Text` value
+    ///
+    pub(crate) fn equality(&self) -> Option<EREv1<32, 16>> {
+        self.equality_ciphertext.clone()
+    }
+
+    /// Determine whether two texts are equal
+    ///
+    pub(crate) fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.base.key_id() != other.base.key_id() {
+            return Err(Error::KeyMismatchError());
+        }
+        self.base.check_normalisation(&other.base)?;
+
+        let lhs = self.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand text has no equality ciphertext".to_string())
+        })?;
+        let rhs = other.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand text has no equality ciphertext".to_string())
+        })?;
+
+        lhs.try_eq(rhs)
+    }
+
+    /// Calculate the plaintext value of the equality hash
+    ///
+    /// As per the version 1 equality hash, except that it is 128 bits long, so that the chance of
+    /// two different texts having the same hash is negligible even in very large datasets.
+    ///
+    fn eq_hash(text: &str, field: &Field) -> Result<u128, Error> {
+        let mut hasher_key: [u8; 32] = Default::default();
+        field.subkey(&mut hasher_key, TEXT_V2_EQUALITY_HASH_KEY_IDENTIFIER)?;
+
+        let hasher = Static::new(&hasher_key)?;
+        let mut hash: [u8; 16] = Default::default();
+        hasher.derive_key(&mut hash, text.as_bytes())?;
+
+        Ok(u128::from_be_bytes(hash))
+    }
+
+    /// Encrypt a given text as though it were a Text value's `equality_ciphertext`
+    ///
+    pub(crate) fn ere_equality(
+        text: &str,
+        normalisation: Normalisation,
+        field: &Field,
+        allow_unsafe: bool,
+    ) -> Result<EREv1<32, 16>, Error> {
+        let hash = Self::eq_hash(&normalisation.apply(text), field)?;
+
+        if allow_unsafe {
+            Ok(EREv1::<32, 16>::new_with_left(
+                hash,
+                TEXT_V2_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+                field,
+            )?)
+        } else {
+            Ok(EREv1::<32, 16>::new(
+                hash,
+                TEXT_V2_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+                field,
+            )?)
+        }
+    }
+}
+
+impl Hash for V2 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base.hash(state);
+    }
+}
+
+impl Ord for V2 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.base.cmp(&other.base)
+    }
+}
+
+impl PartialOrd for V2 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V2 {
+    #[allow(clippy::panic)] // No way to signal error from impl Eq
+    fn eq(&self, other: &Self) -> bool {
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl Eq for V2 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn text(s: &str) -> V2 {
+        V2::new_with_options(s, b"context", &field(), &TextOptions::new()).unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = text("Hello, Enquo!");
+
+        assert_eq!(
+            "Hello, Enquo!",
+            value.base().decrypt(b"context", &field()).unwrap()
+        );
+    }
+
+    #[test]
+    fn equality_hash_is_128_bits() {
+        assert_ne!(
+            0,
+            V2::eq_hash("Hello, Enquo!", &field()).unwrap() >> 64,
+            "upper half of the hash is empty"
+        );
+    }
+
+    #[test]
+    fn ciphertexts_compare_correctly() {
+        let value = text("Hello, Enquo!");
+
+        assert!(value
+            .try_eq(
+                &V2::new_with_options(
+                    "Hello, Enquo!",
+                    b"context",
+                    &field(),
+                    &TextOptions::new().with_unsafe_parts()
+                )
+                .unwrap()
+            )
+            .unwrap());
+        assert!(!value
+            .try_eq(
+                &V2::new_with_options(
+                    "Goodbye, Enquo!",
+                    b"context",
+                    &field(),
+                    &TextOptions::new().with_unsafe_parts()
+                )
+                .unwrap()
+            )
+            .unwrap());
+    }
+
+    #[test]
+    fn base_has_no_equality_ciphertext() {
+        assert!(text("Hello, Enquo!").base().equality().is_none());
+    }
+
+    #[test]
+    fn unqueryable_texts_have_no_equality_ciphertext() {
+        let mut value = text("Hello, Enquo!");
+        value.make_unqueryable();
+
+        assert!(value.equality().is_none());
+        assert!(value.base().length().is_none());
+    }
+
+    #[test]
+    fn ciphertext_survives_serialisation() {
+        let opts = TextOptions::new()
+            .with_unsafe_parts()
+            .with_ordering(8)
+            .with_words();
+        let value = V2::new_with_options("Hello, Enquo!", b"context", &field(), &opts).unwrap();
+
+        let mut s: Vec<u8> = vec![];
+        ciborium::ser::into_writer(&value, &mut s).unwrap();
+        let from_cbor: V2 = ciborium::de::from_reader(&*s).unwrap();
+
+        let json = serde_json::to_string(&value).unwrap();
+        let from_json: V2 = serde_json::from_str(&json).unwrap();
+
+        for v in [from_cbor, from_json] {
+            assert_eq!(
+                "Hello, Enquo!",
+                v.base().decrypt(b"context", &field()).unwrap()
+            );
+            assert!(v.try_eq(&value).unwrap());
+            assert_eq!(Ordering::Equal, v.base().try_cmp(value.base()).unwrap());
+            assert!(v.base().words().is_some());
+        }
+    }
+}
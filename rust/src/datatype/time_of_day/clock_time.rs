@@ -0,0 +1,234 @@
+//! A time of day that is known to exist
+//!
+
+use crate::Error;
+
+/// The number of nanoseconds in a second
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// A valid time of day, on a 24 hour clock, to the nanosecond
+///
+/// This is what [`TimeOfDay::new()`](crate::datatype::TimeOfDay::new) and friends actually
+/// encrypt.  You don't usually need to create one yourself, because anything which can be
+/// converted into a `ClockTime` -- such as an `(h, m, s)` or `(h, m, s, ns)` tuple, or, with the
+/// `chrono` or `time` features enabled, a `chrono::NaiveTime` or `time::Time` -- can be passed
+/// instead.  Likewise, [`TimeOfDay::decrypt_as()`](crate::datatype::TimeOfDay::decrypt_as) can
+/// produce anything that a `ClockTime` can be converted into.
+///
+/// Leap seconds are not supported.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ClockTime {
+    /// The hour, from 0 to 23
+    hour: u8,
+    /// The minute past the hour, from 0 to 59
+    minute: u8,
+    /// The second past the minute, from 0 to 59
+    second: u8,
+    /// Nanoseconds past the second
+    nanosecond: u32,
+}
+
+impl ClockTime {
+    /// Make a new time of day, after checking that it actually exists
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if any of the parts are out of range.
+    ///
+    pub fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Result<Self, Error> {
+        if hour > 23 {
+            return Err(Error::RangeError(format!(
+                "{hour} is not a valid hour (must be 0-23)"
+            )));
+        }
+        if minute > 59 {
+            return Err(Error::RangeError(format!(
+                "{minute} is not a valid minute (must be 0-59)"
+            )));
+        }
+        if second > 59 {
+            return Err(Error::RangeError(format!(
+                "{second} is not a valid second (must be 0-59)"
+            )));
+        }
+        if nanosecond >= NANOS_PER_SECOND {
+            return Err(Error::RangeError(format!(
+                "{nanosecond} nanoseconds is not less than one second"
+            )));
+        }
+
+        Ok(Self {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        })
+    }
+
+    /// The hour, from 0 to 23
+    ///
+    #[must_use]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// The minute past the hour, from 0 to 59
+    ///
+    #[must_use]
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The second past the minute, from 0 to 59
+    ///
+    #[must_use]
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Nanoseconds past the second
+    ///
+    #[must_use]
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+}
+
+impl TryFrom<(u8, u8, u8)> for ClockTime {
+    type Error = Error;
+
+    fn try_from(t: (u8, u8, u8)) -> Result<Self, Error> {
+        let (h, m, s) = t;
+        Self::new(h, m, s, 0)
+    }
+}
+
+impl TryFrom<(u8, u8, u8, u32)> for ClockTime {
+    type Error = Error;
+
+    fn try_from(t: (u8, u8, u8, u32)) -> Result<Self, Error> {
+        let (h, m, s, ns) = t;
+        Self::new(h, m, s, ns)
+    }
+}
+
+impl From<ClockTime> for (u8, u8, u8, u32) {
+    fn from(t: ClockTime) -> Self {
+        (t.hour, t.minute, t.second, t.nanosecond)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveTime> for ClockTime {
+    type Error = Error;
+
+    fn try_from(t: chrono::NaiveTime) -> Result<Self, Error> {
+        use chrono::Timelike;
+
+        let part = |v: u32| {
+            u8::try_from(v).map_err(|e| Error::RangeError(format!("invalid time {t} ({e})")))
+        };
+
+        // Leap seconds show up as nanoseconds >= 1s, and get rejected here
+        Self::new(
+            part(t.hour())?,
+            part(t.minute())?,
+            part(t.second())?,
+            t.nanosecond(),
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<ClockTime> for chrono::NaiveTime {
+    type Error = Error;
+
+    fn try_from(t: ClockTime) -> Result<Self, Error> {
+        chrono::NaiveTime::from_hms_nano_opt(
+            t.hour.into(),
+            t.minute.into(),
+            t.second.into(),
+            t.nanosecond,
+        )
+        .ok_or_else(|| Error::RangeError(format!("{t:?} cannot be represented by chrono")))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::Time> for ClockTime {
+    type Error = Error;
+
+    fn try_from(t: time::Time) -> Result<Self, Error> {
+        Self::new(t.hour(), t.minute(), t.second(), t.nanosecond())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<ClockTime> for time::Time {
+    type Error = Error;
+
+    fn try_from(t: ClockTime) -> Result<Self, Error> {
+        time::Time::from_hms_nano(t.hour, t.minute, t.second, t.nanosecond)
+            .map_err(|e| Error::RangeError(format!("{t:?} cannot be represented by time ({e})")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_times_are_accepted() {
+        for (h, m, s, ns) in [(0, 0, 0, 0), (12, 34, 56, 789), (23, 59, 59, 999_999_999)] {
+            assert!(ClockTime::new(h, m, s, ns).is_ok(), "{h}:{m}:{s}.{ns}");
+        }
+    }
+
+    #[test]
+    fn imaginary_times_are_rejected() {
+        for (h, m, s, ns) in [
+            (24, 0, 0, 0),
+            (0, 60, 0, 0),
+            (0, 0, 60, 0),
+            (0, 0, 0, 1_000_000_000),
+        ] {
+            assert!(
+                matches!(ClockTime::new(h, m, s, ns), Err(Error::RangeError(_))),
+                "{h}:{m}:{s}.{ns}"
+            );
+        }
+    }
+
+    #[test]
+    fn tuples_convert_both_ways() {
+        let t = ClockTime::try_from((9, 30, 0)).unwrap();
+
+        assert_eq!((9, 30, 0, 0), <(u8, u8, u8, u32)>::from(t));
+        assert_eq!(t, ClockTime::try_from((9, 30, 0, 0)).unwrap());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_times_convert_both_ways() {
+        let naive = chrono::NaiveTime::from_hms_nano_opt(17, 45, 3, 250).unwrap();
+        let t = ClockTime::try_from(naive).unwrap();
+
+        assert_eq!((17, 45, 3, 250), <(u8, u8, u8, u32)>::from(t));
+        assert_eq!(naive, chrono::NaiveTime::try_from(t).unwrap());
+        assert!(matches!(
+            ClockTime::try_from(chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 1500).unwrap()),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_times_convert_both_ways() {
+        let tt = time::Time::from_hms_nano(17, 45, 3, 250).unwrap();
+        let t = ClockTime::try_from(tt).unwrap();
+
+        assert_eq!((17, 45, 3, 250), <(u8, u8, u8, u32)>::from(t));
+        assert_eq!(tt, time::Time::try_from(t).unwrap());
+    }
+}
@@ -0,0 +1,491 @@
+//! Store and query times of day in an encrypted form
+//!
+
+mod clock_time;
+mod time_code;
+mod v1;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub use self::{clock_time::ClockTime, time_code::TimeCode};
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted, queryable time of day
+///
+/// A time of day is a time on a 24 hour clock, to the nanosecond, with no date or time zone
+/// attached, such as the start of a shift, or the time a shop opens.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum TimeOfDay {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl TimeOfDay {
+    /// Create a new encrypted, queryable time of day
+    ///
+    /// The time can be anything that converts into a [`ClockTime`], such as an `(h, m, s)` or
+    /// `(h, m, s, ns)` tuple, or (with the appropriate features enabled) a `chrono::NaiveTime` or
+    /// `time::Time`.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the time does not exist (such as 24:00:00),
+    /// and can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new<T>(time: T, context: &[u8], field: &Field) -> Result<TimeOfDay, Error>
+    where
+        T: TryInto<ClockTime>,
+        Error: From<T::Error>,
+    {
+        Ok(TimeOfDay::v1(Box::new(V1::new(
+            time.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Create a new encrypted, queryable time of day with degraded security
+    ///
+    /// The time can be anything that converts into a [`ClockTime`], as per
+    /// [`TimeOfDay::new()`](TimeOfDay::new).
+    ///
+    /// While the time itself is securely encrypted, the ciphertexts produced by this function may
+    /// contain components that allow an attacker to infer the plaintext or some part(s) thereof.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the time does not exist, and can return an
+    /// error if the process of encrypting the data fails.
+    ///
+    pub fn new_with_unsafe_parts<T>(
+        time: T,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<TimeOfDay, Error>
+    where
+        T: TryInto<ClockTime>,
+        Error: From<T::Error>,
+    {
+        Ok(TimeOfDay::v1(Box::new(V1::new_with_unsafe_parts(
+            time.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Decrypt the time of day
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the time could not be decrypted for some reason, such as if the
+    /// wrong field was provided, or the decryption context was incorrect.  See
+    /// [`TimeOfDay::new()`](TimeOfDay::new) for more details about encryption and decryption
+    /// contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<ClockTime, Error> {
+        match self {
+            TimeOfDay::v1(t) => t.decrypt(context, field),
+            TimeOfDay::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the time of day, and convert it into whatever type you like
+    ///
+    /// Anything that a [`ClockTime`] can be converted into will do, such as an `(h, m, s, ns)`
+    /// tuple, or (with the appropriate features enabled) a `chrono::NaiveTime` or `time::Time`.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error in all the same situations as
+    /// [`TimeOfDay::decrypt()`](TimeOfDay::decrypt), as well as if the decrypted time cannot be
+    /// represented by the requested type.
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: TryFrom<ClockTime>,
+        Error: From<T::Error>,
+    {
+        Ok(T::try_from(self.decrypt(context, field)?)?)
+    }
+
+    /// Compare this time with another, without panicking if it can't be done
+    ///
+    /// Comparing times via `Ord` will panic if (say) one of them has been made unqueryable, or
+    /// they came from different fields.  If you'd rather find out about that via an error, this is
+    /// the method for you.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the times were encrypted with different field keys, if either has been
+    /// made unqueryable, if neither has the left ciphertexts required to perform the comparison,
+    /// or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (TimeOfDay::v1(s), TimeOfDay::v1(o)) => s.try_cmp(o),
+            (TimeOfDay::Unknown, _) | (_, TimeOfDay::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this time is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as
+    /// [`TimeOfDay::try_cmp()`](TimeOfDay::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored times of day
+    ///
+    /// This is what you need for range queries, like "appointments between 09:00 and 12:00".  The
+    /// time can be anything that converts into a [`ClockTime`], and is encrypted with the field's
+    /// ORE keys, so the result is suitable for passing to
+    /// [`TimeOfDay::try_cmp_query()`](TimeOfDay::try_cmp_query) for any time encrypted using the
+    /// same field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the time does not exist, and can return an
+    /// error if the encryption process fails.
+    ///
+    pub fn query<T>(time: T, field: &Field) -> Result<Kith<TimeCode>, Error>
+    where
+        T: TryInto<ClockTime>,
+        Error: From<T::Error>,
+    {
+        let mut k = Kith::new();
+        k.add_member(TimeCode::from_ore_v1(
+            V1::ore(time.try_into()?, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare this time with the time represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by
+    /// [`TimeOfDay::query()`](TimeOfDay::query), using the same field as this time.  The result
+    /// says where this time sorts relative to the queried time, so `Ordering::Greater` means that
+    /// this time is later in the day than the queried one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this time, or if the time is of an unknown version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<TimeCode>) -> Result<Ordering, Error> {
+        let code = match self {
+            TimeOfDay::v1(t) => t
+                .ore_parts()
+                .ok()
+                .map(|(hour, minute, second, nanosecond)| {
+                    TimeCode::from_ore_v1(
+                        (
+                            hour.clone(),
+                            minute.clone(),
+                            second.clone(),
+                            nanosecond.clone(),
+                        ),
+                        t.key_id(),
+                    )
+                }),
+            TimeOfDay::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(code, TimeCode::try_cmp)
+    }
+
+    /// Get the encrypted hour of the time
+    ///
+    /// This is returned as an orderable encrypted ciphertext, so you can search for times in a
+    /// particular hour (or range of hours) by comparing it against the values produced by
+    /// [`TimeOfDay::query_hour()`](TimeOfDay::query_hour).
+    ///
+    /// If the time has been made unqueryable, this method will return `None`.
+    ///
+    #[must_use]
+    pub fn hour(&self) -> Option<ORE<1, 32>> {
+        match self {
+            TimeOfDay::v1(t) => t
+                .ore_parts()
+                .ok()
+                .map(|(h, _, _, _)| ORE::from_ore_v1(h.clone(), t.key_id())),
+            TimeOfDay::Unknown => None,
+        }
+    }
+
+    /// Get the encrypted minute-past-the-hour of the time
+    ///
+    /// As per [`TimeOfDay::hour()`](TimeOfDay::hour), but for comparing against the values
+    /// produced by [`TimeOfDay::query_minute()`](TimeOfDay::query_minute), for queries like
+    /// "everything scheduled on the half hour".
+    ///
+    #[must_use]
+    pub fn minute(&self) -> Option<ORE<1, 64>> {
+        match self {
+            TimeOfDay::v1(t) => t
+                .ore_parts()
+                .ok()
+                .map(|(_, m, _, _)| ORE::from_ore_v1(m.clone(), t.key_id())),
+            TimeOfDay::Unknown => None,
+        }
+    }
+
+    /// Get the encrypted second-past-the-minute of the time
+    ///
+    /// As per [`TimeOfDay::hour()`](TimeOfDay::hour), but for comparing against the values
+    /// produced by [`TimeOfDay::query_second()`](TimeOfDay::query_second).
+    ///
+    #[must_use]
+    pub fn second(&self) -> Option<ORE<1, 64>> {
+        match self {
+            TimeOfDay::v1(t) => t
+                .ore_parts()
+                .ok()
+                .map(|(_, _, s, _)| ORE::from_ore_v1(s.clone(), t.key_id())),
+            TimeOfDay::Unknown => None,
+        }
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the hours of times
+    ///
+    /// The result can be compared against the value returned by
+    /// [`TimeOfDay::hour()`](TimeOfDay::hour) for any time encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the hour is not between 0 and 23, and can
+    /// return an error if the encryption process fails.
+    ///
+    pub fn query_hour(hour: u8, field: &Field) -> Result<Kith<ORE<1, 32>>, Error> {
+        if hour > 23 {
+            return Err(Error::RangeError(format!(
+                "{hour} is not a valid hour (must be 0-23)"
+            )));
+        }
+
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V1::ore_hour(hour, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the minutes of times
+    ///
+    /// The result can be compared against the value returned by
+    /// [`TimeOfDay::minute()`](TimeOfDay::minute) for any time encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the minute is not between 0 and 59, and can
+    /// return an error if the encryption process fails.
+    ///
+    pub fn query_minute(minute: u8, field: &Field) -> Result<Kith<ORE<1, 64>>, Error> {
+        if minute > 59 {
+            return Err(Error::RangeError(format!(
+                "{minute} is not a valid minute (must be 0-59)"
+            )));
+        }
+
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V1::ore_minute(minute, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against the seconds of times
+    ///
+    /// The result can be compared against the value returned by
+    /// [`TimeOfDay::second()`](TimeOfDay::second) for any time encrypted using the same field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the second is not between 0 and 59, and can
+    /// return an error if the encryption process fails.
+    ///
+    pub fn query_second(second: u8, field: &Field) -> Result<Kith<ORE<1, 64>>, Error> {
+        if second > 59 {
+            return Err(Error::RangeError(format!(
+                "{second} is not a valid second (must be 0-59)"
+            )));
+        }
+
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V1::ore_second(second, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Remove the ability to perform any queries on this value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted time, without any ability
+    /// to query it.  In that case, you can save a fair chunk of space by calling this method
+    /// before you serialise it.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the object could not be made unqueryable, or if an attempt was made
+    /// to make an Unknown version unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            TimeOfDay::v1(t) => {
+                t.make_unqueryable();
+                Ok(())
+            }
+            TimeOfDay::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl Ord for TimeOfDay {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for TimeOfDay {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for TimeOfDay {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TimeOfDay {}
+
+impl KithDatatype for TimeOfDay {
+    fn key_id(&self) -> KeyId {
+        match self {
+            TimeOfDay::v1(t) => t.key_id(),
+            TimeOfDay::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            TimeOfDay::v1(_) => 1,
+            TimeOfDay::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn component_matches<const N: usize, const W: u16>(
+        component: Option<ORE<N, W>>,
+        query: &Kith<ORE<N, W>>,
+    ) -> Ordering {
+        let c = component.unwrap();
+
+        query
+            .compatible_member(&c)
+            .unwrap()
+            .try_cmp(&c)
+            .unwrap()
+            .reverse()
+    }
+
+    #[test]
+    fn component_queries() {
+        let value = TimeOfDay::new((10, 30, 45), b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Equal,
+            component_matches(value.hour(), &TimeOfDay::query_hour(10, &field()).unwrap())
+        );
+        assert_eq!(
+            Ordering::Equal,
+            component_matches(
+                value.minute(),
+                &TimeOfDay::query_minute(30, &field()).unwrap()
+            )
+        );
+        assert_eq!(
+            Ordering::Greater,
+            component_matches(
+                value.second(),
+                &TimeOfDay::query_second(0, &field()).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn imaginary_times_are_rejected() {
+        assert!(matches!(
+            TimeOfDay::new((24, 0, 0), b"row 1", &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            TimeOfDay::query((0, 60, 0), &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            TimeOfDay::query_hour(24, &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            TimeOfDay::query_minute(60, &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            TimeOfDay::query_second(60, &field()),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn unqueryable_times_have_no_components() {
+        let mut value = TimeOfDay::new((10, 30, 45), b"row 1", &field()).unwrap();
+        value.make_unqueryable().unwrap();
+
+        assert!(value.hour().is_none());
+        assert!(value.minute().is_none());
+        assert!(value.second().is_none());
+    }
+}
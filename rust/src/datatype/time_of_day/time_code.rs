@@ -0,0 +1,120 @@
+//! Encrypted time codes, for range-querying times of day
+//!
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::v1::OreParts;
+use crate::{
+    crypto::OREv1,
+    datatype::kith::{Datatype as KithDatatype, Member as KithMember},
+    field::KeyId,
+    Error,
+};
+
+/// Versioning support
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[allow(clippy::large_enum_variant, clippy::missing_docs_in_private_items)] // Unknown is only a placeholder
+enum Ciphertext {
+    #[allow(non_camel_case_types)]
+    v1 {
+        #[serde(rename = "h")]
+        hour: OREv1<1, 32>,
+        #[serde(rename = "m")]
+        minute: OREv1<1, 64>,
+        #[serde(rename = "s")]
+        second: OREv1<1, 64>,
+        #[serde(rename = "n")]
+        nanosecond: OREv1<4, 256>,
+    },
+    Unknown,
+}
+
+/// The encrypted components of a time of day, for comparing against stored times
+///
+/// This is what you get from [`TimeOfDay::query()`](crate::datatype::TimeOfDay::query), to
+/// compare against stored times with
+/// [`TimeOfDay::try_cmp_query()`](crate::datatype::TimeOfDay::try_cmp_query).
+///
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeCode {
+    /// The ORE ciphertexts of the hour, minute, second, and nanosecond
+    #[serde(rename = "o")]
+    ore_ciphertexts: Ciphertext,
+
+    /// The field key ID which was used to generate this ciphertext
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+impl TimeCode {
+    /// Create a time code from the `OREv1` ciphertexts of a time's components
+    ///
+    pub(crate) fn from_ore_v1(parts: OreParts, key_id: KeyId) -> Self {
+        let (hour, minute, second, nanosecond) = parts;
+
+        Self {
+            ore_ciphertexts: Ciphertext::v1 {
+                hour,
+                minute,
+                second,
+                nanosecond,
+            },
+            kid: key_id.into(),
+        }
+    }
+
+    /// Compare this time code with another
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time codes were encrypted with different field keys, if neither
+    /// time code contains left ciphertexts, or if either time code is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        match (&self.ore_ciphertexts, &other.ore_ciphertexts) {
+            (
+                Ciphertext::v1 {
+                    hour: lh,
+                    minute: lm,
+                    second: ls,
+                    nanosecond: ln,
+                },
+                Ciphertext::v1 {
+                    hour: rh,
+                    minute: rm,
+                    second: rs,
+                    nanosecond: rn,
+                },
+            ) => Ok(lh
+                .try_cmp(rh)?
+                .then(lm.try_cmp(rm)?)
+                .then(ls.try_cmp(rs)?)
+                .then(ln.try_cmp(rn)?)),
+            (Ciphertext::Unknown, _) | (_, Ciphertext::Unknown) => {
+                Err(Error::UnknownVersionError())
+            }
+        }
+    }
+}
+
+impl KithMember for TimeCode {}
+
+impl KithDatatype for TimeCode {
+    fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self.ore_ciphertexts {
+            Ciphertext::v1 { .. } => 1,
+            Ciphertext::Unknown => 0,
+        }
+    }
+}
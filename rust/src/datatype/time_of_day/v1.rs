@@ -0,0 +1,353 @@
+//! The first version of the `TimeOfDay` datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use super::ClockTime;
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The value in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the hour
+    #[serde(rename = "h")]
+    hour_ciphertext: Option<OREv1<1, 32>>,
+    /// An orderable encrypted form of the minute-past-the-hour
+    #[serde(rename = "m")]
+    minute_ciphertext: Option<OREv1<1, 64>>,
+    /// An orderable encrypted form of the second-past-the-minute
+    #[serde(rename = "s")]
+    second_ciphertext: Option<OREv1<1, 64>>,
+    /// An orderable encrypted form of the nanoseconds-past-the-second
+    #[serde(rename = "n")]
+    nanosecond_ciphertext: Option<OREv1<4, 256>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// All the ORE ciphertexts of a time, in order of significance
+pub(crate) type OreParts = (OREv1<1, 32>, OREv1<1, 64>, OREv1<1, 64>, OREv1<4, 256>);
+
+/// What's actually encrypted into the AES ciphertext
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTime {
+    /// The hour
+    h: u8,
+    /// The minute
+    m: u8,
+    /// The second
+    s: u8,
+    /// The nanoseconds
+    n: u32,
+}
+
+/// Prefix of the identifiers for the subkeys used to encrypt the components of the time
+const TIME_OF_DAY_V1_ORE_KEY_PREFIX: &[u8] = b"TimeOfDayV1.ore_key";
+
+impl V1 {
+    /// Encrypt the time
+    pub(crate) fn new(time: ClockTime, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(time, context, field, false)
+    }
+
+    /// Encrypt the time in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        time: ClockTime,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(time, context, field, true)
+    }
+
+    /// Encrypt the whole time for decryption, and each of its components for querying
+    ///
+    fn encrypt(
+        time: ClockTime,
+        context: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
+        let s_time = StoredTime {
+            h: time.hour(),
+            m: time.minute(),
+            s: time.second(),
+            n: time.nanosecond(),
+        };
+
+        let v = cbor!(s_time).map_err(|e| {
+            Error::EncodingError(format!("failed to convert time to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode time value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        let (hour, minute, second, nanosecond) = Self::ore(time, field, include_left)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            hour_ciphertext: Some(hour),
+            minute_ciphertext: Some(minute),
+            second_ciphertext: Some(second),
+            nanosecond_ciphertext: Some(nanosecond),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt all the components of a time into order-revealing ciphertexts
+    ///
+    pub(crate) fn ore(
+        time: ClockTime,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OreParts, Error> {
+        Ok((
+            Self::ore_hour(time.hour(), field, include_left)?,
+            Self::ore_minute(time.minute(), field, include_left)?,
+            Self::ore_second(time.second(), field, include_left)?,
+            Self::ore_component(time.nanosecond(), b".nanosecond", field, include_left)?,
+        ))
+    }
+
+    /// Encrypt an hour into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_hour(
+        h: u8,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<1, 32>, Error> {
+        Self::ore_component(h, b".hour", field, include_left)
+    }
+
+    /// Encrypt a minute-past-the-hour into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_minute(
+        m: u8,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<1, 64>, Error> {
+        Self::ore_component(m, b".minute", field, include_left)
+    }
+
+    /// Encrypt a second-past-the-minute into an order-revealing ciphertext
+    ///
+    pub(crate) fn ore_second(
+        s: u8,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<1, 64>, Error> {
+        Self::ore_component(s, b".second", field, include_left)
+    }
+
+    /// Encrypt one component of a time, using the subkey for that component
+    ///
+    fn ore_component<T, const N: usize, const W: u16>(
+        v: T,
+        component: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<N, W>, Error>
+    where
+        cretrit::PlainText<N, W>: TryFrom<T>,
+        <cretrit::PlainText<N, W> as TryFrom<T>>::Error: std::fmt::Display,
+    {
+        let mut subkey_id = TIME_OF_DAY_V1_ORE_KEY_PREFIX.to_vec();
+        subkey_id.extend_from_slice(component);
+
+        if include_left {
+            OREv1::<N, W>::new_with_left(v, &subkey_id, field)
+        } else {
+            OREv1::<N, W>::new(v, &subkey_id, field)
+        }
+    }
+
+    /// Turn the ciphertext back into a time
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<ClockTime, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let s_time = ciborium::de::from_reader::<StoredTime, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        ClockTime::new(s_time.h, s_time.m, s_time.s, s_time.n)
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Strip out everything that makes a queryable `TimeOfDay` queryable, leaving just the
+    /// readable encrypted value behind
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.hour_ciphertext = None;
+        self.minute_ciphertext = None;
+        self.second_ciphertext = None;
+        self.nanosecond_ciphertext = None;
+    }
+
+    /// Extract the various encrypted components, for comparison purposes
+    #[allow(clippy::type_complexity)] // It's a tuple of four things, it's fine
+    pub(crate) fn ore_parts(
+        &self,
+    ) -> Result<(&OREv1<1, 32>, &OREv1<1, 64>, &OREv1<1, 64>, &OREv1<4, 256>), Error> {
+        let missing =
+            |c: &str| Error::MissingComponentError(format!("cannot extract '{c}' from ciphertext"));
+
+        Ok((
+            self.hour_ciphertext
+                .as_ref()
+                .ok_or_else(|| missing("hour"))?,
+            self.minute_ciphertext
+                .as_ref()
+                .ok_or_else(|| missing("minute"))?,
+            self.second_ciphertext
+                .as_ref()
+                .ok_or_else(|| missing("second"))?,
+            self.nanosecond_ciphertext
+                .as_ref()
+                .ok_or_else(|| missing("nanosecond"))?,
+        ))
+    }
+
+    /// Compare two times, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let (lh, lm, ls, ln) = self.ore_parts()?;
+        let (rh, rm, rs, rn) = other.ore_parts()?;
+
+        Ok(lh
+            .try_cmp(rh)?
+            .then(lm.try_cmp(rm)?)
+            .then(ls.try_cmp(rs)?)
+            .then(ln.try_cmp(rn)?))
+    }
+}
+
+impl Ord for V1 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V1 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn t(h: u8, m: u8, s: u8, n: u32) -> ClockTime {
+        ClockTime::new(h, m, s, n).unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V1::new(t(13, 37, 0, 42), b"context", &field()).unwrap();
+
+        assert_eq!(
+            t(13, 37, 0, 42),
+            value.decrypt(b"context", &field()).unwrap()
+        );
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(t(13, 37, 0, 42), b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(t(13, 37, 0, 42), b"somecontext", &field()).unwrap();
+        let (h, m, s, n) = value.ore_parts().unwrap();
+
+        assert!(!h.has_left());
+        assert!(!m.has_left());
+        assert!(!s.has_left());
+        assert!(!n.has_left());
+    }
+
+    #[test]
+    fn times_compare_correctly() {
+        let values = [
+            t(0, 0, 0, 0),
+            t(0, 0, 0, 1),
+            t(0, 0, 1, 0),
+            t(0, 1, 0, 0),
+            t(9, 0, 0, 0),
+            t(12, 0, 0, 0),
+            t(23, 59, 59, 999_999_999),
+        ];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l:?} vs {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn unqueryable_times_cannot_be_compared() {
+        let value = V1::new_with_unsafe_parts(t(13, 37, 0, 42), b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::new(t(13, 37, 0, 42), b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+        assert_eq!(
+            t(13, 37, 0, 42),
+            unqueryable.decrypt(b"ctx", &field()).unwrap()
+        );
+    }
+}
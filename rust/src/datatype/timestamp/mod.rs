@@ -0,0 +1,328 @@
+//! Store and query instants in time in an encrypted form
+//!
+
+mod unix_timestamp;
+mod v1;
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+pub use self::unix_timestamp::UnixTimestamp;
+
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ORE,
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted, queryable timestamp
+///
+/// A timestamp refers to a single instant in time, to the nanosecond, such as when a record was
+/// created or last updated.  Timestamps are ordered by the instant they refer to; if the
+/// timestamp was created from a value that carried a UTC offset, that offset is encrypted along
+/// with the timestamp, so it can be recovered on decryption, but it has no effect on ordering.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum Timestamp {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl Timestamp {
+    /// Create a new encrypted, queryable timestamp
+    ///
+    /// The timestamp can be anything that converts into a [`UnixTimestamp`], such as an `i64`
+    /// count of seconds since the Unix epoch, a `std::time::SystemTime`, or (with the appropriate
+    /// features enabled) a `chrono::DateTime` or `time::OffsetDateTime`.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `UnixTimestamp`, and can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new<T>(ts: T, context: &[u8], field: &Field) -> Result<Timestamp, Error>
+    where
+        T: TryInto<UnixTimestamp>,
+        Error: From<T::Error>,
+    {
+        Ok(Timestamp::v1(Box::new(V1::new(
+            ts.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Create a new encrypted, queryable timestamp with degraded security
+    ///
+    /// The timestamp can be anything that converts into a [`UnixTimestamp`], as per
+    /// [`Timestamp::new()`](Timestamp::new).
+    ///
+    /// While the timestamp itself is securely encrypted, the ciphertexts produced by this
+    /// function may contain components that allow an attacker to infer, either precisely or
+    /// approximately, the plaintext value.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `UnixTimestamp`, and can return an error if the process of encrypting the data fails.
+    ///
+    pub fn new_with_unsafe_parts<T>(
+        ts: T,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<Timestamp, Error>
+    where
+        T: TryInto<UnixTimestamp>,
+        Error: From<T::Error>,
+    {
+        Ok(Timestamp::v1(Box::new(V1::new_with_unsafe_parts(
+            ts.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Decrypt the timestamp
+    ///
+    /// If the timestamp was created with a UTC offset, the offset is included in the result.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the timestamp could not be decrypted for some reason, such as if
+    /// the wrong field was provided, or the decryption context was incorrect.  See
+    /// [`Timestamp::new()`](Timestamp::new) for more details about encryption and decryption
+    /// contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<UnixTimestamp, Error> {
+        match self {
+            Timestamp::v1(t) => t.decrypt(context, field),
+            Timestamp::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the timestamp, and convert it into whatever type you like
+    ///
+    /// Anything that a [`UnixTimestamp`] can be converted into will do, such as a
+    /// `std::time::SystemTime`, or (with the appropriate features enabled) a `chrono::DateTime`
+    /// or `time::OffsetDateTime`.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error in all the same situations as
+    /// [`Timestamp::decrypt()`](Timestamp::decrypt), as well as if the decrypted timestamp cannot
+    /// be represented by the requested type.
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: TryFrom<UnixTimestamp>,
+        Error: From<T::Error>,
+    {
+        Ok(T::try_from(self.decrypt(context, field)?)?)
+    }
+
+    /// Compare this timestamp with another, without panicking if it can't be done
+    ///
+    /// Comparing timestamps via `Ord` will panic if (say) one of them has been made unqueryable,
+    /// or they came from different fields.  If you'd rather find out about that via an error,
+    /// this is the method for you.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timestamps were encrypted with different field keys, if either has
+    /// been made unqueryable, if neither has the left ciphertext required to perform the
+    /// comparison, or if either is of an unknown version.
+    ///
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Timestamp::v1(s), Timestamp::v1(o)) => s.try_cmp(o),
+            (Timestamp::Unknown, _) | (_, Timestamp::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Determine whether this timestamp is equal to another, without panicking if it can't be
+    /// done
+    ///
+    /// Timestamps are equal if they refer to the same instant, regardless of their UTC offsets.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as
+    /// [`Timestamp::try_cmp()`](Timestamp::try_cmp).
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        Ok(self.try_cmp(other)? == Ordering::Equal)
+    }
+
+    /// Generate a set of encrypted values suitable for comparing against stored timestamps
+    ///
+    /// This is what you need for range queries, like `WHERE created_at > NOW() - '1 day'`.  The
+    /// timestamp can be anything that converts into a [`UnixTimestamp`], and is encrypted with the
+    /// field's ORE key, so the result is suitable for passing to
+    /// [`Timestamp::try_cmp_query()`](Timestamp::try_cmp_query) for any timestamp encrypted using
+    /// the same field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the value cannot be converted into a
+    /// `UnixTimestamp`, and can return an error if the encryption process fails.
+    ///
+    pub fn query<T>(ts: T, field: &Field) -> Result<Kith<ORE<12, 256>>, Error>
+    where
+        T: TryInto<UnixTimestamp>,
+        Error: From<T::Error>,
+    {
+        let mut k = Kith::new();
+        k.add_member(ORE::from_ore_v1(
+            V1::ore(ts.try_into()?, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Compare this timestamp with the timestamp represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by
+    /// [`Timestamp::query()`](Timestamp::query), using the same field as this timestamp.  The
+    /// result says where this timestamp sorts relative to the queried one, so `Ordering::Greater`
+    /// means that this timestamp is later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timestamp has been made unqueryable, if the query does not contain
+    /// a value which can be compared against this timestamp, or if the timestamp is of an unknown
+    /// version.
+    ///
+    pub fn try_cmp_query(&self, query: &Kith<ORE<12, 256>>) -> Result<Ordering, Error> {
+        let ore = match self {
+            Timestamp::v1(t) => t
+                .ore_ciphertext()
+                .map(|o| ORE::from_ore_v1(o.clone(), t.key_id())),
+            Timestamp::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(ore, ORE::try_cmp)
+    }
+
+    /// Remove the ability to perform any queries on this value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted timestamp, without any
+    /// ability to query it.  In that case, you can save a fair chunk of space by calling this
+    /// method before you serialise it.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the object could not be made unqueryable, or if an attempt was made
+    /// to make an Unknown version unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            Timestamp::v1(t) => {
+                t.make_unqueryable();
+                Ok(())
+            }
+            Timestamp::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl Ord for Timestamp {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl KithDatatype for Timestamp {
+    fn key_id(&self) -> KeyId {
+        match self {
+            Timestamp::v1(t) => t.key_id(),
+            Timestamp::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            Timestamp::v1(_) => 1,
+            Timestamp::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    #[test]
+    fn queries_ignore_the_offset() {
+        let local = UnixTimestamp::new(1_700_000_000, 0)
+            .unwrap()
+            .with_offset(36_000)
+            .unwrap();
+        let created_at = Timestamp::new(local, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            Ordering::Equal,
+            created_at
+                .try_cmp_query(&Timestamp::query(1_700_000_000, &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Ordering::Less,
+            created_at
+                .try_cmp_query(&Timestamp::query((1_700_000_000, 1), &field()).unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            Some(36_000),
+            created_at.decrypt(b"row 1", &field()).unwrap().offset()
+        );
+    }
+
+    #[test]
+    fn invalid_timestamps_are_rejected() {
+        assert!(matches!(
+            Timestamp::new((0, 1_000_000_000), b"row 1", &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            Timestamp::query((0, 1_000_000_000), &field()),
+            Err(Error::RangeError(_))
+        ));
+    }
+}
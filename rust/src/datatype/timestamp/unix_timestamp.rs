@@ -0,0 +1,317 @@
+//! A point in time, in a form that everything can agree on
+//!
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Error;
+
+/// The number of nanoseconds in a second
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+/// The largest UTC offset (in either direction) that we'll accept
+const MAX_OFFSET_SECONDS: i32 = 86_399;
+
+/// An instant in time, with nanosecond precision, and optionally the UTC offset it was recorded in
+///
+/// This is what [`Timestamp::new()`](crate::datatype::Timestamp::new) and friends actually
+/// encrypt.  You don't usually need to create one yourself, because anything which can be
+/// converted into a `UnixTimestamp` -- such as a number of seconds since the Unix epoch, a
+/// `std::time::SystemTime`, or, with the `chrono` or `time` features enabled, a
+/// `chrono::DateTime` or `time::OffsetDateTime` -- can be passed instead.
+///
+/// The offset doesn't change *when* the timestamp is, only how it was written down, so two
+/// timestamps that refer to the same instant sort equally, regardless of their offsets.  The
+/// offset is only kept so that it can be handed back on decryption.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnixTimestamp {
+    /// Whole seconds since 1970-01-01T00:00:00Z, not counting leap seconds
+    secs: i64,
+    /// Nanoseconds past the second
+    nanos: u32,
+    /// The UTC offset that the timestamp was originally expressed in, in seconds east of UTC
+    offset: Option<i32>,
+}
+
+impl UnixTimestamp {
+    /// Make a new timestamp, from the number of seconds (and nanoseconds) since the Unix epoch
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if `nanos` is a second or more.
+    ///
+    pub fn new(secs: i64, nanos: u32) -> Result<Self, Error> {
+        if nanos >= NANOS_PER_SECOND {
+            return Err(Error::RangeError(format!(
+                "{nanos} nanoseconds is not less than one second"
+            )));
+        }
+
+        Ok(Self {
+            secs,
+            nanos,
+            offset: None,
+        })
+    }
+
+    /// Record the UTC offset (in seconds east of UTC) that the timestamp was expressed in
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RangeError`](Error::RangeError) if the offset is a day or more.
+    ///
+    pub fn with_offset(self, offset: i32) -> Result<Self, Error> {
+        if !(-MAX_OFFSET_SECONDS..=MAX_OFFSET_SECONDS).contains(&offset) {
+            return Err(Error::RangeError(format!(
+                "UTC offset of {offset} seconds is not less than one day"
+            )));
+        }
+
+        Ok(Self {
+            offset: Some(offset),
+            ..self
+        })
+    }
+
+    /// Whole seconds since the Unix epoch
+    ///
+    #[must_use]
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Nanoseconds past the second
+    ///
+    #[must_use]
+    pub fn nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    /// The UTC offset, in seconds east of UTC, if one was recorded
+    ///
+    #[must_use]
+    pub fn offset(&self) -> Option<i32> {
+        self.offset
+    }
+}
+
+impl From<i64> for UnixTimestamp {
+    fn from(secs: i64) -> Self {
+        Self {
+            secs,
+            nanos: 0,
+            offset: None,
+        }
+    }
+}
+
+impl TryFrom<(i64, u32)> for UnixTimestamp {
+    type Error = Error;
+
+    fn try_from(ts: (i64, u32)) -> Result<Self, Error> {
+        let (secs, nanos) = ts;
+        Self::new(secs, nanos)
+    }
+}
+
+impl TryFrom<SystemTime> for UnixTimestamp {
+    type Error = Error;
+
+    fn try_from(t: SystemTime) -> Result<Self, Error> {
+        let out_of_range =
+            |e| Error::RangeError(format!("{t:?} is too far from the Unix epoch ({e})"));
+
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => Self::new(
+                i64::try_from(d.as_secs()).map_err(out_of_range)?,
+                d.subsec_nanos(),
+            ),
+            Err(e) => {
+                // Before the epoch, so count backwards, while keeping the nanoseconds positive
+                let d = e.duration();
+                let secs = i64::try_from(d.as_secs()).map_err(out_of_range)?;
+
+                let (secs, nanos) = match d.subsec_nanos() {
+                    0 => (secs.checked_neg(), 0),
+                    n => (
+                        secs.checked_neg().and_then(|s| s.checked_sub(1)),
+                        NANOS_PER_SECOND.saturating_sub(n),
+                    ),
+                };
+
+                Self::new(
+                    secs.ok_or_else(|| {
+                        Error::RangeError(format!("{t:?} is too far before the Unix epoch"))
+                    })?,
+                    nanos,
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<UnixTimestamp> for SystemTime {
+    type Error = Error;
+
+    fn try_from(ts: UnixTimestamp) -> Result<Self, Error> {
+        let out_of_range =
+            || Error::RangeError(format!("{ts:?} cannot be represented as a SystemTime"));
+        let d = Duration::new(ts.secs.unsigned_abs(), 0);
+
+        let whole = if ts.secs < 0 {
+            UNIX_EPOCH.checked_sub(d)
+        } else {
+            UNIX_EPOCH.checked_add(d)
+        }
+        .ok_or_else(out_of_range)?;
+
+        whole
+            .checked_add(Duration::new(0, ts.nanos))
+            .ok_or_else(out_of_range)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for UnixTimestamp {
+    type Error = Error;
+
+    fn try_from(dt: chrono::DateTime<chrono::Utc>) -> Result<Self, Error> {
+        // Leap seconds show up as nanos >= 1s, which we quite deliberately refuse
+        Self::new(dt.timestamp(), dt.timestamp_subsec_nanos())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::FixedOffset>> for UnixTimestamp {
+    type Error = Error;
+
+    fn try_from(dt: chrono::DateTime<chrono::FixedOffset>) -> Result<Self, Error> {
+        Self::new(dt.timestamp(), dt.timestamp_subsec_nanos())?
+            .with_offset(dt.offset().local_minus_utc())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<UnixTimestamp> for chrono::DateTime<chrono::Utc> {
+    type Error = Error;
+
+    fn try_from(ts: UnixTimestamp) -> Result<Self, Error> {
+        chrono::DateTime::from_timestamp(ts.secs, ts.nanos)
+            .ok_or_else(|| Error::RangeError(format!("{ts:?} cannot be represented by chrono")))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<UnixTimestamp> for chrono::DateTime<chrono::FixedOffset> {
+    type Error = Error;
+
+    fn try_from(ts: UnixTimestamp) -> Result<Self, Error> {
+        let offset =
+            chrono::FixedOffset::east_opt(ts.offset.unwrap_or_default()).ok_or_else(|| {
+                Error::RangeError(format!("{ts:?} has an offset chrono cannot represent"))
+            })?;
+
+        Ok(chrono::DateTime::<chrono::Utc>::try_from(ts)?.with_timezone(&offset))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for UnixTimestamp {
+    type Error = Error;
+
+    fn try_from(dt: time::OffsetDateTime) -> Result<Self, Error> {
+        Self::new(dt.unix_timestamp(), dt.nanosecond())?.with_offset(dt.offset().whole_seconds())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<UnixTimestamp> for time::OffsetDateTime {
+    type Error = Error;
+
+    fn try_from(ts: UnixTimestamp) -> Result<Self, Error> {
+        let out_of_range =
+            |e| Error::RangeError(format!("{ts:?} cannot be represented by time ({e})"));
+
+        let offset = time::UtcOffset::from_whole_seconds(ts.offset.unwrap_or_default())
+            .map_err(out_of_range)?;
+
+        Ok(time::OffsetDateTime::from_unix_timestamp(ts.secs)
+            .map_err(out_of_range)?
+            .replace_nanosecond(ts.nanos)
+            .map_err(out_of_range)?
+            .to_offset(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nanos_must_be_less_than_a_second() {
+        assert!(UnixTimestamp::new(0, 999_999_999).is_ok());
+        assert!(matches!(
+            UnixTimestamp::new(0, 1_000_000_000),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn offsets_must_be_less_than_a_day() {
+        let ts = UnixTimestamp::from(0);
+
+        assert_eq!(Some(-36_000), ts.with_offset(-36_000).unwrap().offset());
+        assert!(matches!(ts.with_offset(86_400), Err(Error::RangeError(_))));
+        assert!(matches!(ts.with_offset(-86_400), Err(Error::RangeError(_))));
+    }
+
+    #[test]
+    fn system_times_convert_both_ways() {
+        for (secs, nanos) in [(0, 0), (1_700_000_000, 123), (-1, 0), (-2, 500_000_000)] {
+            let ts = UnixTimestamp::new(secs, nanos).unwrap();
+            let st = SystemTime::try_from(ts).unwrap();
+
+            assert_eq!(ts, UnixTimestamp::try_from(st).unwrap());
+        }
+
+        assert_eq!(
+            UnixTimestamp::new(-2, 500_000_000).unwrap(),
+            UnixTimestamp::try_from(UNIX_EPOCH - Duration::from_millis(1500)).unwrap()
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_datetimes_convert_both_ways() {
+        let dt = chrono::DateTime::parse_from_rfc3339("2023-11-14T22:13:20.5+10:00").unwrap();
+        let ts = UnixTimestamp::try_from(dt).unwrap();
+
+        assert_eq!(1_699_964_000, ts.secs());
+        assert_eq!(500_000_000, ts.nanos());
+        assert_eq!(Some(36_000), ts.offset());
+        assert_eq!(
+            dt,
+            chrono::DateTime::<chrono::FixedOffset>::try_from(ts).unwrap()
+        );
+        assert_eq!(
+            dt.with_timezone(&chrono::Utc),
+            chrono::DateTime::<chrono::Utc>::try_from(ts).unwrap()
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_datetimes_convert_both_ways() {
+        let dt = time::OffsetDateTime::from_unix_timestamp_nanos(1_699_964_000_500_000_000)
+            .unwrap()
+            .to_offset(time::UtcOffset::from_hms(-5, -30, 0).unwrap());
+        let ts = UnixTimestamp::try_from(dt).unwrap();
+
+        assert_eq!(1_699_964_000, ts.secs());
+        assert_eq!(Some(-19_800), ts.offset());
+
+        let back = time::OffsetDateTime::try_from(ts).unwrap();
+        assert_eq!(dt, back);
+        assert_eq!(dt.offset(), back.offset());
+    }
+}
@@ -0,0 +1,317 @@
+//! The first version of the Timestamp datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::cmp::Ordering;
+
+use super::UnixTimestamp;
+use crate::{
+    crypto::{AES256v1, OREv1},
+    field::KeyId,
+    util::secs_and_nanos_to_u128,
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The timestamp in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An orderable encrypted form of the instant the timestamp refers to
+    #[serde(rename = "o")]
+    ore_ciphertext: Option<OREv1<12, 256>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// What's actually encrypted into the AES ciphertext, offset and all
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTimestamp {
+    /// Seconds since the epoch
+    s: i64,
+    /// Nanoseconds past the second
+    n: u32,
+    /// The original UTC offset, if any
+    o: Option<i32>,
+}
+
+/// Identifier for the subkey used to encrypt the ORE ciphertext
+const TIMESTAMP_V1_ORE_KEY_IDENTIFIER: &[u8] = b"TimestampV1.ore_key";
+
+impl V1 {
+    /// Encrypt the timestamp
+    pub(crate) fn new(ts: UnixTimestamp, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(ts, context, field, false)
+    }
+
+    /// Encrypt the timestamp in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        ts: UnixTimestamp,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(ts, context, field, true)
+    }
+
+    /// Encrypt the whole timestamp for decryption, and the instant it refers to for querying
+    ///
+    fn encrypt(
+        ts: UnixTimestamp,
+        context: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
+        let s_ts = StoredTimestamp {
+            s: ts.secs(),
+            n: ts.nanos(),
+            o: ts.offset(),
+        };
+
+        let v = cbor!(s_ts).map_err(|e| {
+            Error::EncodingError(format!(
+                "failed to convert timestamp to ciborium value: {e}"
+            ))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode timestamp value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            ore_ciphertext: Some(Self::ore(ts, field, include_left)?),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt the instant referred to by a timestamp into an order-revealing ciphertext
+    ///
+    /// The UTC offset plays no part, as it doesn't change when the timestamp refers to.
+    ///
+    pub(crate) fn ore(
+        ts: UnixTimestamp,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<OREv1<12, 256>, Error> {
+        let u = secs_and_nanos_to_u128(ts.secs(), ts.nanos())?;
+
+        if include_left {
+            Ok(OREv1::<12, 256>::new_with_left(
+                u,
+                TIMESTAMP_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        } else {
+            Ok(OREv1::<12, 256>::new(
+                u,
+                TIMESTAMP_V1_ORE_KEY_IDENTIFIER,
+                field,
+            )?)
+        }
+    }
+
+    /// Turn the ciphertext back into a timestamp
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<UnixTimestamp, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let s_ts = ciborium::de::from_reader::<StoredTimestamp, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        let ts = UnixTimestamp::new(s_ts.s, s_ts.n)?;
+
+        match s_ts.o {
+            Some(o) => ts.with_offset(o),
+            None => Ok(ts),
+        }
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Return the ORE ciphertext, if the timestamp hasn't been made unqueryable
+    pub(crate) fn ore_ciphertext(&self) -> Option<&OREv1<12, 256>> {
+        self.ore_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.ore_ciphertext = None;
+    }
+
+    /// Compare two timestamps, returning an error if the comparison can't be done
+    pub(crate) fn try_cmp(&self, other: &Self) -> Result<Ordering, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no ORE ciphertext".to_string())
+        })?;
+        let rhs = other.ore_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no ORE ciphertext".to_string())
+        })?;
+
+        lhs.try_cmp(rhs)
+    }
+}
+
+impl Ord for V1 {
+    #[allow(clippy::panic)] // No way to signal error from impl Ord
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.try_cmp(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl PartialOrd for V1 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for V1 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for V1 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn ts(secs: i64, nanos: u32) -> UnixTimestamp {
+        UnixTimestamp::new(secs, nanos).unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V1::new(ts(1_700_000_000, 42), b"context", &field()).unwrap();
+
+        assert_eq!(
+            ts(1_700_000_000, 42),
+            value.decrypt(b"context", &field()).unwrap()
+        );
+    }
+
+    #[test]
+    fn offset_round_trips() {
+        let original = ts(1_700_000_000, 42).with_offset(-18_000).unwrap();
+        let value = V1::new(original, b"context", &field()).unwrap();
+
+        assert_eq!(
+            Some(-18_000),
+            value.decrypt(b"context", &field()).unwrap().offset()
+        );
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(ts(0, 0), b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(ts(0, 0), b"somecontext", &field()).unwrap();
+
+        assert!(!value.ore_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn extremes_can_be_encrypted() {
+        let min = V1::new_with_unsafe_parts(ts(i64::MIN, 0), b"ctx", &field()).unwrap();
+        let max = V1::new(ts(i64::MAX, 999_999_999), b"ctx", &field()).unwrap();
+
+        assert_eq!(Ordering::Less, min.try_cmp(&max).unwrap());
+    }
+
+    #[test]
+    fn timestamps_compare_correctly() {
+        let values = [
+            ts(-1, 999_999_999),
+            ts(0, 0),
+            ts(0, 1),
+            ts(1, 0),
+            ts(1_700_000_000, 0),
+        ];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i.cmp(&j), lhs.try_cmp(&rhs).unwrap(), "{l:?} vs {r:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_do_not_affect_ordering() {
+        let utc = V1::new_with_unsafe_parts(ts(1_700_000_000, 0), b"row 1", &field()).unwrap();
+        let local = V1::new(
+            ts(1_700_000_000, 0).with_offset(36_000).unwrap(),
+            b"row 2",
+            &field(),
+        )
+        .unwrap();
+
+        assert_eq!(Ordering::Equal, utc.try_cmp(&local).unwrap());
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let other_field = Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"baz")
+        .unwrap();
+
+        let value = V1::new_with_unsafe_parts(ts(0, 0), b"somecontext", &field()).unwrap();
+        let other_key = V1::new_with_unsafe_parts(ts(0, 0), b"somecontext", &other_field).unwrap();
+        let safe = V1::new(ts(0, 0), b"somecontext", &field()).unwrap();
+        let mut unqueryable = V1::new(ts(0, 0), b"somecontext", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            value.try_cmp(&other_key),
+            Err(Error::KeyMismatchError())
+        ));
+        assert!(matches!(
+            safe.try_cmp(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_cmp(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
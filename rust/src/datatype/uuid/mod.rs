@@ -0,0 +1,276 @@
+//! An encrypted UUID, which can be queried for equality
+//!
+
+mod uuid_bytes;
+mod v1;
+
+use serde::{Deserialize, Serialize};
+
+pub use self::uuid_bytes::UuidBytes;
+use self::v1::V1;
+use crate::{
+    datatype::kith::{Datatype as KithDatatype, Kith},
+    datatype::ERE,
+    field::KeyId,
+    Error, Field,
+};
+
+/// The encrypted UUID
+///
+/// UUIDs are, for all practical purposes, opaque identifiers, so the only query an encrypted UUID
+/// supports is "is this the same UUID as that one?".  Unlike the numeric types, there's no ORE
+/// ciphertext in here, so nothing about the *order* of UUIDs is revealed -- which matters for
+/// version 7 UUIDs and their friends, where the order gives away roughly when they were created.
+///
+/// Anything that can be turned into a [`UuidBytes`] can be encrypted, including strings in the
+/// usual hyphenated form, `[u8; 16]`, `u128`, and, with the `uuid` feature enabled,
+/// `uuid::Uuid`.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(missing_docs, clippy::missing_docs_in_private_items)] // I think we can figure it out from the name
+#[non_exhaustive]
+pub enum Uuid {
+    #[allow(non_camel_case_types)]
+    v1(Box<V1>),
+    Unknown,
+}
+
+impl Uuid {
+    /// Create a new encrypted, queryable UUID
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the value can't be converted into a UUID, or if the process of
+    /// encrypting the data fails.
+    ///
+    pub fn new<U>(u: U, context: &[u8], field: &Field) -> Result<Uuid, Error>
+    where
+        U: TryInto<UuidBytes>,
+        Error: From<U::Error>,
+    {
+        Ok(Uuid::v1(Box::new(V1::new(u.try_into()?, context, field)?)))
+    }
+
+    /// Create a new encrypted, queryable UUID with degraded security
+    ///
+    /// While the value itself is securely encrypted, the ciphertexts produced by this function may
+    /// contain components that allow an attacker to infer, either precisely or approximately, the
+    /// plaintext value.
+    ///
+    /// See [the Enquo threat model](https://enquo.org/threat-models/) for more details.
+    ///
+    #[doc = include_str!("../encryption_contexts.md")]
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the value can't be converted into a UUID, or if the process of
+    /// encrypting the data fails.
+    ///
+    pub fn new_with_unsafe_parts<U>(u: U, context: &[u8], field: &Field) -> Result<Uuid, Error>
+    where
+        U: TryInto<UuidBytes>,
+        Error: From<U::Error>,
+    {
+        Ok(Uuid::v1(Box::new(V1::new_with_unsafe_parts(
+            u.try_into()?,
+            context,
+            field,
+        )?)))
+    }
+
+    /// Decrypt the UUID
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the value could not be successfully decrypted, which may happen
+    /// because the wrong field was used, or because the decryption context was incorrect.  See
+    /// [`Uuid::new()`](Uuid::new) for more details about encryption and decryption contexts.
+    ///
+    pub fn decrypt(&self, context: &[u8], field: &Field) -> Result<UuidBytes, Error> {
+        match self {
+            Uuid::v1(u) => u.decrypt(context, field),
+            Uuid::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Decrypt the UUID into some other representation, such as a `uuid::Uuid` or a `String`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in all the same situations as [`Uuid::decrypt()`](Uuid::decrypt).
+    ///
+    pub fn decrypt_as<T>(&self, context: &[u8], field: &Field) -> Result<T, Error>
+    where
+        T: From<UuidBytes>,
+    {
+        Ok(self.decrypt(context, field)?.into())
+    }
+
+    /// Determine whether this UUID is equal to another, without panicking if it can't be done
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UUIDs were encrypted with different field keys, if either has been
+    /// made unqueryable, if neither has the left ciphertext required to perform the comparison,
+    /// or if either is of an unknown version.
+    ///
+    pub fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        match (self, other) {
+            (Uuid::v1(s), Uuid::v1(o)) => s.try_eq(o),
+            (Uuid::Unknown, _) | (_, Uuid::Unknown) => Err(Error::UnknownVersionError()),
+        }
+    }
+
+    /// Generate a set of encrypted values suitable for finding stored UUIDs equal to the given one
+    ///
+    /// This is what you need for `WHERE id = '...'` queries.  The result is suitable for passing
+    /// to [`Uuid::try_eq_query()`](Uuid::try_eq_query) for any UUID encrypted using the same
+    /// field, regardless of the context it was encrypted with.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the value can't be converted into a UUID, or if the encryption
+    /// process fails.
+    ///
+    pub fn query<U>(u: U, field: &Field) -> Result<Kith<ERE<32, 16>>, Error>
+    where
+        U: TryInto<UuidBytes>,
+        Error: From<U::Error>,
+    {
+        let mut k = Kith::new();
+        k.add_member(ERE::from_ere_v1(
+            V1::ere(u.try_into()?, field, true)?,
+            field.key_id()?,
+        ));
+        Ok(k)
+    }
+
+    /// Determine whether this UUID is the one represented by a query
+    ///
+    /// The `query` is a set of encrypted values produced by [`Uuid::query()`](Uuid::query), using
+    /// the same field as this UUID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UUID has been made unqueryable, if the query does not contain a
+    /// value which can be compared against this UUID, or if the UUID is of an unknown version.
+    ///
+    pub fn try_eq_query(&self, query: &Kith<ERE<32, 16>>) -> Result<bool, Error> {
+        let ere = match self {
+            Uuid::v1(u) => u
+                .equality_ciphertext()
+                .map(|e| ERE::from_ere_v1(e.clone(), u.key_id())),
+            Uuid::Unknown => return Err(Error::UnknownVersionError()),
+        };
+
+        query.compare_with(ere, ERE::try_eq)
+    }
+
+    /// Remove the ability to perform any queries on this encrypted value
+    ///
+    /// Sometimes you just want to be able to store a safely encrypted UUID, without any ability
+    /// to query it.  In that case, you can save some space by calling this method before
+    /// serialisation.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if for some unfathomable reason the ciphertext cannot be made
+    /// unqueryable, such as if an attempt is made to make an Unknown version ciphertext
+    /// unqueryable.
+    ///
+    pub fn make_unqueryable(&mut self) -> Result<(), Error> {
+        match self {
+            Uuid::v1(u) => {
+                u.make_unqueryable();
+                Ok(())
+            }
+            Uuid::Unknown => Err(Error::UnknownVersionError()),
+        }
+    }
+}
+
+impl PartialEq for Uuid {
+    #[allow(clippy::panic)] // No way to signal error from impl PartialEq
+    fn eq(&self, other: &Self) -> bool {
+        self.try_eq(other).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl Eq for Uuid {}
+
+impl KithDatatype for Uuid {
+    fn key_id(&self) -> KeyId {
+        match self {
+            Uuid::v1(u) => u.key_id(),
+            Uuid::Unknown => Default::default(),
+        }
+    }
+
+    fn ciphertext_version(&self) -> u32 {
+        match self {
+            Uuid::v1(_) => 1,
+            Uuid::Unknown => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{key_provider::Static, Root};
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    const ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    #[test]
+    fn invalid_uuids_are_rejected() {
+        assert!(matches!(
+            Uuid::new("not-a-uuid", b"row 1", &field()),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            Uuid::query("not-a-uuid", &field()),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn equality_query() {
+        let value = Uuid::new(ID, b"row 1", &field()).unwrap();
+
+        assert!(value
+            .try_eq_query(&Uuid::query(ID, &field()).unwrap())
+            .unwrap());
+        assert!(!value
+            .try_eq_query(&Uuid::query(0u128, &field()).unwrap())
+            .unwrap());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_crate_values_round_trip() {
+        let id = ::uuid::Uuid::parse_str(ID).unwrap();
+        let value = Uuid::new(id, b"row 1", &field()).unwrap();
+
+        assert_eq!(
+            id,
+            value
+                .decrypt_as::<::uuid::Uuid>(b"row 1", &field())
+                .unwrap()
+        );
+        assert!(value
+            .try_eq_query(&Uuid::query(ID, &field()).unwrap())
+            .unwrap());
+    }
+}
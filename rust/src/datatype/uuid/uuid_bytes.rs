@@ -0,0 +1,178 @@
+//! The sixteen bytes that make up a UUID
+//!
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Error;
+
+/// A UUID, as the 16 bytes that make it up
+///
+/// This is what [`Uuid::new()`](crate::datatype::Uuid::new) and friends actually encrypt.  You
+/// don't usually need to create one yourself, because anything which can be converted into a
+/// `UuidBytes` -- such as a `[u8; 16]`, a `u128`, a string like
+/// `"67e55044-10b1-426f-9247-bb680e5fe0c8"`, or, with the `uuid` feature enabled, a
+/// `uuid::Uuid` -- can be passed instead.
+///
+/// The bytes are in the same order as they appear in the usual hyphenated form, which is also how
+/// the `uuid` crate stores them.  No attempt is made to check the version or variant bits, so
+/// any 16 bytes will do.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UuidBytes([u8; 16]);
+
+impl UuidBytes {
+    /// Make a new UUID from its bytes
+    ///
+    #[must_use]
+    pub fn new(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    /// The bytes that make up the UUID
+    ///
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl fmt::Display for UuidBytes {
+    /// Write the UUID out in the usual lowercase, hyphenated form
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if [4, 6, 8, 10].contains(&i) {
+                f.write_str("-")?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for UuidBytes {
+    type Err = Error;
+
+    /// Parse a UUID in hyphenated (`67e55044-10b1-426f-9247-bb680e5fe0c8`) or simple
+    /// (`67e5504410b1426f9247bb680e5fe0c8`) form, in either case
+    ///
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::RangeError(format!("{s:?} is not a valid UUID"));
+
+        let hex: String = if s.len() == 36
+            && s.char_indices()
+                .all(|(i, c)| (c == '-') == [8, 13, 18, 23].contains(&i))
+        {
+            s.chars().filter(|c| *c != '-').collect()
+        } else {
+            s.to_owned()
+        };
+
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        u128::from_str_radix(&hex, 16)
+            .map(Self::from)
+            .map_err(|e| Error::RangeError(format!("{s:?} is not a valid UUID ({e})")))
+    }
+}
+
+impl TryFrom<&str> for UuidBytes {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Error> {
+        s.parse()
+    }
+}
+
+impl From<[u8; 16]> for UuidBytes {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<UuidBytes> for [u8; 16] {
+    fn from(u: UuidBytes) -> Self {
+        u.0
+    }
+}
+
+impl From<u128> for UuidBytes {
+    fn from(u: u128) -> Self {
+        Self(u.to_be_bytes())
+    }
+}
+
+impl From<UuidBytes> for u128 {
+    fn from(u: UuidBytes) -> Self {
+        u128::from_be_bytes(u.0)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<::uuid::Uuid> for UuidBytes {
+    fn from(u: ::uuid::Uuid) -> Self {
+        Self(u.into_bytes())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<UuidBytes> for ::uuid::Uuid {
+    fn from(u: UuidBytes) -> Self {
+        ::uuid::Uuid::from_bytes(u.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HYPHENATED: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    #[test]
+    fn strings_round_trip() {
+        let u: UuidBytes = HYPHENATED.parse().unwrap();
+
+        assert_eq!(HYPHENATED, u.to_string());
+        assert_eq!(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8, u128::from(u));
+    }
+
+    #[test]
+    fn other_spellings_are_accepted() {
+        let u: UuidBytes = HYPHENATED.parse().unwrap();
+
+        assert_eq!(u, "67E55044-10B1-426F-9247-BB680E5FE0C8".parse().unwrap());
+        assert_eq!(u, "67e5504410b1426f9247bb680e5fe0c8".parse().unwrap());
+    }
+
+    #[test]
+    fn nonsense_is_rejected() {
+        for s in [
+            "",
+            "67e55044-10b1-426f-9247-bb680e5fe0c",
+            "67e55044-10b1-426f-9247-bb680e5fe0c8a",
+            "67e5504410b1-426f-9247-bb680e5fe0c8-",
+            "67e55044-10b1-426f-9247-bb680e5fe0cg",
+            "+7e5504410b1426f9247bb680e5fe0c8",
+            "{67e55044-10b1-426f-9247-bb680e5fe0c8}",
+        ] {
+            assert!(
+                matches!(s.parse::<UuidBytes>(), Err(Error::RangeError(_))),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids_convert_both_ways() {
+        let uuid = ::uuid::Uuid::parse_str(HYPHENATED).unwrap();
+        let u = UuidBytes::from(uuid);
+
+        assert_eq!(HYPHENATED, u.to_string());
+        assert_eq!(uuid, ::uuid::Uuid::from(u));
+    }
+}
@@ -0,0 +1,241 @@
+//! The first version of the Uuid datatype
+//!
+
+use ciborium::cbor;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use super::UuidBytes;
+use crate::{
+    crypto::{AES256v1, EREv1},
+    field::KeyId,
+    key_provider::{KeyProvider, Static},
+    Error, Field,
+};
+
+/// The ciphertext and all its components
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize)]
+#[doc(hidden)]
+pub struct V1 {
+    /// The UUID in a form that can be decrypted again when needed
+    #[serde(rename = "a")]
+    aes_ciphertext: AES256v1,
+    /// An encrypted form of the UUID's equality hash, which can only be compared for equality
+    #[serde(rename = "e")]
+    equality_ciphertext: Option<EREv1<32, 16>>,
+    /// A serialisation-friendly form of the field key ID
+    #[serde(rename = "k", with = "serde_bytes")]
+    kid: Vec<u8>,
+}
+
+/// Identifier for the subkey used to calculate the plaintext value of the equality hash
+const UUID_V1_EQUALITY_HASH_KEY_IDENTIFIER: &[u8] = b"UuidV1.equality_hash_key";
+/// Identifier for the subkey used to encrypt the equality hash
+const UUID_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER: &[u8] =
+    b"UuidV1.equality_hash_key_ciphertext";
+
+impl V1 {
+    /// Encrypt the UUID
+    pub(crate) fn new(u: UuidBytes, context: &[u8], field: &Field) -> Result<V1, Error> {
+        Self::encrypt(u, context, field, false)
+    }
+
+    /// Encrypt the UUID in a degraded security form
+    pub(crate) fn new_with_unsafe_parts(
+        u: UuidBytes,
+        context: &[u8],
+        field: &Field,
+    ) -> Result<V1, Error> {
+        Self::encrypt(u, context, field, true)
+    }
+
+    /// Encrypt the UUID for decryption, and its keyed hash for equality queries
+    ///
+    fn encrypt(
+        u: UuidBytes,
+        context: &[u8],
+        field: &Field,
+        include_left: bool,
+    ) -> Result<V1, Error> {
+        let v = cbor!(serde_bytes::Bytes::new(u.as_bytes())).map_err(|e| {
+            Error::EncodingError(format!("failed to convert UUID to ciborium value: {e}"))
+        })?;
+
+        let mut msg: Vec<u8> = Default::default();
+        ciborium::ser::into_writer(&v, &mut msg)
+            .map_err(|e| Error::EncodingError(format!("failed to encode UUID value: {e}")))?;
+
+        let aes = AES256v1::new(&msg, context, field)?;
+
+        Ok(V1 {
+            aes_ciphertext: aes,
+            equality_ciphertext: Some(Self::ere(u, field, include_left)?),
+            kid: field.key_id()?.into(),
+        })
+    }
+
+    /// Encrypt a UUID into an equality-revealing ciphertext
+    ///
+    /// Rather than encrypting the UUID itself, it's run through a 128-bit keyed hash first, the
+    /// same as version 2 of `Text` does.  This stops UUIDs that share a prefix (such as version 7
+    /// UUIDs created at around the same time) from giving that away, and the hash is long enough
+    /// that two different UUIDs having the same one isn't something that'll happen in practice.
+    ///
+    pub(crate) fn ere(
+        u: UuidBytes,
+        field: &Field,
+        include_left: bool,
+    ) -> Result<EREv1<32, 16>, Error> {
+        let mut hasher_key: [u8; 32] = Default::default();
+        field.subkey(&mut hasher_key, UUID_V1_EQUALITY_HASH_KEY_IDENTIFIER)?;
+
+        let hasher = Static::new(&hasher_key)?;
+        let mut hash: [u8; 16] = Default::default();
+        hasher.derive_key(&mut hash, u.as_bytes())?;
+        let hash = u128::from_be_bytes(hash);
+
+        if include_left {
+            EREv1::<32, 16>::new_with_left(
+                hash,
+                UUID_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER,
+                field,
+            )
+        } else {
+            EREv1::<32, 16>::new(hash, UUID_V1_EQUALITY_HASH_CIPHERTEXT_KEY_IDENTIFIER, field)
+        }
+    }
+
+    /// Turn the ciphertext back into a UUID
+    pub(crate) fn decrypt(&self, context: &[u8], field: &Field) -> Result<UuidBytes, Error> {
+        let pt = self.aes_ciphertext.decrypt(context, field)?;
+
+        let bytes = ciborium::de::from_reader::<serde_bytes::ByteBuf, &[u8]>(&*pt)
+            .map_err(|e| Error::DecodingError(format!("could not decode decrypted value: {e}")))?;
+
+        Ok(UuidBytes::new(bytes.into_vec().try_into().map_err(
+            |b: Vec<u8>| Error::DecodingError(format!("decrypted UUID is {} bytes long", b.len())),
+        )?))
+    }
+
+    /// Get the field key ID in canonical form
+    pub(crate) fn key_id(&self) -> KeyId {
+        let mut key_id: KeyId = Default::default();
+        key_id.copy_from_slice(&self.kid);
+        key_id
+    }
+
+    /// Return the equality ciphertext, if the UUID hasn't been made unqueryable
+    pub(crate) fn equality_ciphertext(&self) -> Option<&EREv1<32, 16>> {
+        self.equality_ciphertext.as_ref()
+    }
+
+    /// Strip out everything that makes the "queryable" bit work
+    pub(crate) fn make_unqueryable(&mut self) {
+        self.equality_ciphertext = None;
+    }
+
+    /// Determine whether two UUIDs are equal, returning an error if the comparison can't be done
+    pub(crate) fn try_eq(&self, other: &Self) -> Result<bool, Error> {
+        if self.kid != other.kid {
+            return Err(Error::KeyMismatchError());
+        }
+
+        let lhs = self.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("left-hand value has no equality ciphertext".to_string())
+        })?;
+        let rhs = other.equality_ciphertext.as_ref().ok_or_else(|| {
+            Error::MissingComponentError("right-hand value has no equality ciphertext".to_string())
+        })?;
+
+        lhs.try_eq(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Root;
+    use std::sync::Arc;
+
+    fn field() -> Field {
+        Root::new(Arc::new(
+            Static::new(b"this is a suuuuper long test key").unwrap(),
+        ))
+        .unwrap()
+        .field(b"foo", b"bar")
+        .unwrap()
+    }
+
+    fn u(s: &str) -> UuidBytes {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn value_round_trips() {
+        let value = V1::new(
+            u("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            b"context",
+            &field(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            u("67e55044-10b1-426f-9247-bb680e5fe0c8"),
+            value.decrypt(b"context", &field()).unwrap()
+        );
+    }
+
+    #[test]
+    fn incorrect_context_fails() {
+        let value = V1::new(UuidBytes::from(42), b"somecontext", &field()).unwrap();
+
+        let err = value.decrypt(b"othercontext", &field()).err();
+        assert!(matches!(err, Some(Error::DecryptionError(_))));
+    }
+
+    #[test]
+    fn default_encryption_is_safe() {
+        let value = V1::new(UuidBytes::from(42), b"somecontext", &field()).unwrap();
+
+        assert!(!value.equality_ciphertext().unwrap().has_left());
+    }
+
+    #[test]
+    fn uuids_compare_for_equality() {
+        let values = [
+            UuidBytes::from(0),
+            UuidBytes::from(1),
+            u("018f6f1e-3c2a-7000-8000-000000000000"),
+            u("018f6f1e-3c2a-7000-8000-000000000001"),
+            UuidBytes::from(u128::MAX),
+        ];
+
+        for (i, l) in values.iter().enumerate() {
+            let lhs = V1::new_with_unsafe_parts(*l, b"row 1", &field()).unwrap();
+
+            for (j, r) in values.iter().enumerate() {
+                let rhs = V1::new(*r, b"row 2", &field()).unwrap();
+
+                assert_eq!(i == j, lhs.try_eq(&rhs).unwrap(), "{l} vs {r}");
+            }
+        }
+    }
+
+    #[test]
+    fn comparison_errors_are_reported() {
+        let value = V1::new_with_unsafe_parts(UuidBytes::from(42), b"ctx", &field()).unwrap();
+        let safe = V1::new(UuidBytes::from(42), b"ctx", &field()).unwrap();
+        let mut unqueryable = V1::new(UuidBytes::from(42), b"ctx", &field()).unwrap();
+        unqueryable.make_unqueryable();
+
+        assert!(matches!(
+            safe.try_eq(&safe),
+            Err(Error::MissingLeftCiphertextError())
+        ));
+        assert!(matches!(
+            value.try_eq(&unqueryable),
+            Err(Error::MissingComponentError(_))
+        ));
+    }
+}
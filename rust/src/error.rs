@@ -24,8 +24,28 @@ pub enum Error {
     OperationError(String),
     #[error("failed to collate text string: {0}")]
     CollationError(String),
+    #[error("cannot compare texts that were ordered using different collations ({0})")]
+    CollationMismatchError(String),
+    #[error("cannot compare texts that were normalised using different forms ({0})")]
+    NormalisationMismatchError(String),
+    #[error("cannot compare decimals that were encrypted with different scales ({0})")]
+    ScaleMismatchError(String),
+    #[error("cannot compare ciphertexts of different versions ({0})")]
+    VersionMismatchError(String),
     #[error("overflow detected {0}")]
     OverflowError(String),
     #[error("attempted operation on data value with Unknown version")]
     UnknownVersionError(),
+    #[error("cannot compare ciphertexts that were encrypted with different keys")]
+    KeyMismatchError(),
+    #[error("ciphertext does not contain a required component: {0}")]
+    MissingComponentError(String),
+    #[error("cannot compare ciphertexts when neither has a left part")]
+    MissingLeftCiphertextError(),
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(e: std::convert::Infallible) -> Self {
+        match e {}
+    }
 }
@@ -13,10 +13,10 @@
 //!   data that you may wish to encrypt and query.
 //!
 
+pub mod collator;
 pub mod datatype;
 pub mod key_provider;
 
-mod collator;
 mod crypto;
 mod error;
 mod field;
@@ -10,3 +10,46 @@ pub(crate) fn check_overflow<T>(v: (T, bool), e: &str) -> Result<T, Error> {
         (_, true) => Err(Error::OverflowError(e.to_string())),
     }
 }
+
+/// The value that needs to be added/subtracted to turn an i64 into a u64 (and vice versa)
+const I64_OFFSET: i128 = 0x8000_0000_0000_0000;
+
+/// The number of nanoseconds in a second
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// Turn a signed number of seconds, plus some nanoseconds, into an unsigned number of
+/// nanoseconds that sorts the same way
+///
+/// The seconds are offset to make them unsigned, then the whole thing is turned into a count of
+/// nanoseconds, which fits comfortably into 96 bits.
+///
+pub(crate) fn secs_and_nanos_to_u128(secs: i64, nanos: u32) -> Result<u128, Error> {
+    let u: u128 = check_overflow(
+        i128::from(secs).overflowing_add(I64_OFFSET),
+        "while offsetting seconds",
+    )?
+    .try_into()
+    .map_err(|e| Error::EncodingError(format!("failed to convert seconds {secs} to u128 ({e})")))?;
+
+    let u = check_overflow(
+        u.overflowing_mul(NANOS_PER_SECOND),
+        "while converting seconds to nanoseconds",
+    )?;
+
+    check_overflow(
+        u.overflowing_add(u128::from(nanos)),
+        "while adding nanoseconds",
+    )
+}
+
+/// The bit that needs to be flipped to turn an i128 into a u128 (and vice versa)
+const I128_SIGN_BIT: u128 = 0x8000_0000_0000_0000_0000_0000_0000_0000;
+
+/// Turn a signed 128-bit integer into an unsigned one that sorts the same way
+///
+/// This is the same trick as offsetting an i64 by 2^63, except that there's no wider type to do
+/// the arithmetic in, so we flip the sign bit instead (which amounts to the same thing).
+///
+pub(crate) fn i128_to_orderable_u128(i: i128) -> u128 {
+    u128::from_be_bytes(i.to_be_bytes()) ^ I128_SIGN_BIT
+}